@@ -0,0 +1,218 @@
+use std::{
+    fs::{self, Permissions},
+    path::Path,
+    process::Command,
+    io::ErrorKind,
+    os::unix::fs::{self as unix_fs, MetadataExt, PermissionsExt},
+};
+use crate::util;
+use crate::manifest::Attributes;
+use crate::backup::Snapshot;
+use crate::ignore::Ignore;
+use crate::error::AlpsError;
+
+// Modes a fresh copy ends up with anyway, left out of the manifest.
+const FILE_MODES: [u32; 2] = [0o644, 0o755];
+const DIR_MODE: u32 = 0o755;
+
+// Walks a live config, following it if it is a link itself but not the links below it.
+pub(crate) fn record(live: &Path, ignore: &Ignore)-> Result<Vec<Attributes>, AlpsError>{
+    // Owned by whoever runs alps.
+    let user = fs::metadata("/proc/self")?;
+    let mut attributes = Vec::new();
+
+    record_into(live, "", (user.uid(), user.gid()), ignore, &mut attributes)?;
+    Ok(attributes)
+}
+
+fn record_into(path: &Path, relative: &str, user: (u32, u32), ignore: &Ignore, attributes: &mut Vec<Attributes>)-> Result<(), AlpsError>{
+    if ignore.matches(Path::new(relative)){
+        return Ok(());
+    }
+
+    let metadata = if relative.is_empty(){
+        fs::metadata(path)?
+    }
+    else{
+        path.symlink_metadata()?
+    };
+
+    if metadata.is_symlink(){
+        let link = fs::read_link(path)?.display().to_string();
+        attributes.push(Attributes{ path: relative.to_owned(), link: Some(link), ..Attributes::default() });
+        return Ok(());
+    }
+
+    let mode = metadata.mode() & 0o7777;
+    let usual = if metadata.is_dir(){
+        mode == DIR_MODE
+    }
+    else{
+        FILE_MODES.contains(&mode)
+    };
+
+    let entry = Attributes{
+        path: relative.to_owned(),
+        mode: (!usual).then(|| format!("{mode:o}")),
+        owner: (metadata.uid() != user.0).then(|| name("/etc/passwd", metadata.uid())),
+        group: (metadata.gid() != user.1).then(|| name("/etc/group", metadata.gid())),
+        link: None,
+    };
+    if entry != (Attributes{ path: relative.to_owned(), ..Attributes::default() }){
+        attributes.push(entry);
+    }
+
+    if metadata.is_dir(){
+        let mut entries = fs::read_dir(path)?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries{
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let relative = if relative.is_empty(){ name } else{ relative.to_owned() + "/" + &name };
+            record_into(&entry.path(), &relative, user, ignore, attributes)?;
+        }
+    }
+    Ok(())
+}
+
+// The links under target that differ from the recorded ones, as (path, link).
+pub(crate) fn missing_links(target: &Path, attributes: &[Attributes])-> Vec<(String, String)>{
+    attributes.iter()
+        .filter_map(|entry|{
+            let path = join(target, &entry.path);
+            let link = entry.link.as_ref()?;
+            (!fs::read_link(&path).is_ok_and(|current| current == Path::new(link)))
+                .then(|| (path, link.to_owned()))
+        })
+        .collect()
+}
+
+// Puts back what record saw under target once its files are copied. Links replace whatever
+// is in their way, backed up by the snapshot like any other file.
+pub(crate) fn apply(target: &Path, attributes: &[Attributes], snapshot: &mut Snapshot)-> Result<(), AlpsError>{
+    for (path, link) in missing_links(target, attributes){
+        let path = Path::new(&path);
+
+        if path.symlink_metadata().is_ok(){
+            snapshot.replace(path)?;
+        }
+        else{
+            snapshot.save(path)?;
+        }
+
+        if let Some(parent) = path.parent(){
+            fs::create_dir_all(parent)?;
+        }
+        unix_fs::symlink(link, path)?;
+    }
+
+    for entry in attributes.iter().filter(|entry| entry.link.is_none()){
+        let path = join(target, &entry.path);
+        let metadata = fs::metadata(&path)?;
+
+        if let Some(mode) = &entry.mode{
+            let mode = u32::from_str_radix(mode, 8)
+                .map_err(|_| AlpsError::invalid("mode", mode))?;
+
+            if metadata.mode() & 0o7777 != mode{
+                set_mode(Path::new(&path), mode)?;
+            }
+        }
+
+        if entry.owner.is_some() || entry.group.is_some(){
+            let uid = entry.owner.as_deref().map(|owner| id("/etc/passwd", "owner", owner)).transpose()?;
+            let gid = entry.group.as_deref().map(|group| id("/etc/group", "group", group)).transpose()?;
+
+            if uid.is_some_and(|uid| uid != metadata.uid()) || gid.is_some_and(|gid| gid != metadata.gid()){
+                set_owner(Path::new(&path), entry, uid, gid)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Puts the recorded links into a stored config, which leaves them out, for configs whose
+// stored copy is what gets linked to. Anything but a link in the way is left alone.
+pub(crate) fn link_stored(stored: &Path, attributes: &[Attributes])-> Result<(), AlpsError>{
+    for (path, link) in missing_links(stored, attributes){
+        let path = Path::new(&path);
+
+        match path.symlink_metadata(){
+            Ok(metadata) if metadata.is_symlink() => fs::remove_file(path)?,
+            Ok(_) => continue,
+            Err(_) =>{
+                if let Some(parent) = path.parent(){
+                    fs::create_dir_all(parent)?;
+                }
+            }
+        }
+        unix_fs::symlink(link, path)?;
+    }
+    Ok(())
+}
+
+// Gives a written file the mode of the stored one, as a copy would.
+pub(crate) fn copy_mode(source: &Path, target: &Path)-> Result<(), AlpsError>{
+    let mode = fs::metadata(source)?.mode() & 0o7777;
+
+    if fs::metadata(target)?.mode() & 0o7777 != mode{
+        set_mode(target, mode)?;
+    }
+    Ok(())
+}
+
+fn set_mode(path: &Path, mode: u32)-> Result<(), AlpsError>{
+    match fs::set_permissions(path, Permissions::from_mode(mode)){
+        Err(error) if error.kind() == ErrorKind::PermissionDenied =>{
+            util::sudo(Command::new("sudo").arg("chmod").arg(format!("{mode:o}")).arg(path), path)
+        }
+        result => Ok(result?),
+    }
+}
+
+fn set_owner(path: &Path, entry: &Attributes, uid: Option<u32>, gid: Option<u32>)-> Result<(), AlpsError>{
+    match unix_fs::chown(path, uid, gid){
+        Err(error) if error.kind() == ErrorKind::PermissionDenied =>{
+            let owner = entry.owner.as_deref().unwrap_or("").to_owned()
+                + &entry.group.as_deref().map(|group| ":".to_owned() + group).unwrap_or_default();
+
+            util::sudo(Command::new("sudo").arg("chown").arg(owner).arg(path), path)
+        }
+        result => Ok(result?),
+    }
+}
+
+fn join(target: &Path, relative: &str)-> String{
+    if relative.is_empty(){
+        target.display().to_string()
+    }
+    else{
+        target.join(relative).display().to_string()
+    }
+}
+
+// User or group name of an id from /etc/passwd or /etc/group, the id itself when unnamed.
+fn name(file: &str, id: u32)-> String{
+    fs::read_to_string(file)
+        .ok()
+        .and_then(|text|{
+            text.lines()
+                .map(|line| line.split(':').collect::<Vec<&str>>())
+                .find(|fields| fields.len() > 2 && fields[2] == id.to_string())
+                .map(|fields| fields[0].to_owned())
+        })
+        .unwrap_or_else(|| id.to_string())
+}
+
+fn id(file: &str, kind: &str, name: &str)-> Result<u32, AlpsError>{
+    if let Ok(id) = name.parse::<u32>(){
+        return Ok(id);
+    }
+
+    fs::read_to_string(file)?
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<&str>>())
+        .find(|fields| fields.len() > 2 && fields[0] == name)
+        .and_then(|fields| fields[2].parse().ok())
+        .ok_or_else(|| AlpsError::invalid(kind, name))
+}
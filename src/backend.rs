@@ -0,0 +1,146 @@
+mod pacman;
+mod apt;
+mod dnf;
+mod aur;
+mod cargo;
+mod pipx;
+mod npm;
+
+pub use pacman::Pacman;
+pub use apt::Apt;
+pub use dnf::Dnf;
+pub use aur::Aur;
+pub use cargo::Cargo;
+pub use pipx::Pipx;
+pub use npm::Npm;
+
+use std::env;
+use crate::util;
+use crate::error::AlpsError;
+
+pub const BACKENDS: [&str; 3] = ["pacman", "apt", "dnf"];
+pub const AUR_HELPERS: [&str; 2] = ["paru", "yay"];
+pub const TOOLS: [(&str, &str); 3] = [
+    ("cargo", "[CARGO]"),
+    ("pipx", "[PIPX]"),
+    ("npm", "[NPM]")
+];
+
+pub trait PackageBackend{
+    fn name(&self)-> &str;
+    fn available(&self)-> bool;
+    fn query_installed(&self, package: &str)-> bool;
+    fn resolve_group(&self, group: &str)-> Option<Vec<String>>;
+    fn exists(&self, package: &str)-> bool;
+    fn install(&self, packages: &[String])-> Result<(), AlpsError>;
+    fn remove(&self, packages: &[String])-> Result<(), AlpsError>;
+
+    // The argument installing a package at a version, None for backends that can't pin
+    // one. Those skip pinned packages rather than install another version.
+    fn pinned(&self, _package: &str, _version: &str)-> Option<String>{
+        None
+    }
+
+    // Whether the installed package is at a version, asked of backends that pin.
+    fn query_version(&self, _package: &str, _version: &str)-> bool{
+        false
+    }
+
+    // Whether an installed package has a newer version available. Backends that can't
+    // tell report everything as up to date.
+    fn outdated(&self, _package: &str)-> bool{
+        false
+    }
+}
+
+pub fn from_name(name: &str)-> Option<Box<dyn PackageBackend>>{
+    match name{
+        "pacman" => Some(Box::new(Pacman)),
+        "apt" => Some(Box::new(Apt)),
+        "dnf" => Some(Box::new(Dnf)),
+        _ => None,
+    }
+}
+
+pub fn from_tool(name: &str)-> Option<Box<dyn PackageBackend>>{
+    match name{
+        "cargo" => Some(Box::new(Cargo)),
+        "pipx" => Some(Box::new(Pipx)),
+        "npm" => Some(Box::new(Npm)),
+        _ => None,
+    }
+}
+
+// Tool entries pin versions as <name>@<version>. The leading @ of scoped npm packages
+// (@scope/name) is part of the name, not a pin.
+pub fn split_version(entry: &str)-> (&str, Option<&str>){
+    match entry.rsplit_once('@'){
+        Some((name, version)) if !name.is_empty() && !version.is_empty() => (name, Some(version)),
+        _ => (entry, None),
+    }
+}
+
+pub fn detect()-> Option<Box<dyn PackageBackend>>{
+    BACKENDS.iter()
+        .filter_map(|name| from_name(name))
+        .find(|backend| backend.available())
+}
+
+pub fn aur_helper()-> Option<Aur>{
+    let aur = match env::var("ALPS_AUR_HELPER"){
+        Ok(helper) => Aur::new(&helper),
+        Err(_) =>{
+            let helper = AUR_HELPERS.iter()
+                .find(|helper| util::in_path(helper))?;
+
+            Aur::new(helper)
+        }
+    };
+
+    if aur.available(){
+        Some(aur)
+    }
+    else{
+        None
+    }
+}
+
+pub fn select(group: &str, home_dir: &str)-> Result<Box<dyn PackageBackend>, AlpsError>{
+    let text = util::read_label("[BACKEND]", group, home_dir)?;
+    let name = util::get_entries(&text).next();
+
+    match name{
+        Some(name) => from_name(name).ok_or_else(|| AlpsError::invalid("backend", name)),
+        None =>{
+            detect().ok_or_else(||{
+                AlpsError::backend(
+                    "package manager",
+                    "none of (".to_owned() + &BACKENDS.join(", ") + ") found on system, use -Ib to set one"
+                )
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    #[test]
+    fn split_version_pins(){
+        assert_eq!(split_version("ripgrep"), ("ripgrep", None));
+        assert_eq!(split_version("black@24.3.0"), ("black", Some("24.3.0")));
+    }
+
+    #[test]
+    fn split_version_scoped_npm(){
+        assert_eq!(split_version("@types/node"), ("@types/node", None));
+        assert_eq!(split_version("@types/node@20.1.0"), ("@types/node", Some("20.1.0")));
+    }
+
+    #[test]
+    fn split_version_empty_parts(){
+        assert_eq!(split_version("typescript@"), ("typescript@", None));
+        assert_eq!(split_version("@"), ("@", None));
+    }
+}
@@ -0,0 +1,91 @@
+use std::process::Command;
+use crate::util;
+use crate::error::AlpsError;
+use super::PackageBackend;
+
+pub struct Apt;
+
+impl PackageBackend for Apt{
+    fn name(&self)-> &str{
+        "apt"
+    }
+
+    fn available(&self)-> bool{
+        util::in_path("apt-get") && util::in_path("dpkg-query")
+    }
+
+    fn query_installed(&self, package: &str)-> bool{
+        Command::new("dpkg-query")
+            .args(["-W", "-f=${Status}", package])
+            .output()
+            .map(|handle|{
+                handle.status.success()
+                    && String::from_utf8_lossy(&handle.stdout).ends_with("install ok installed")
+            })
+            .unwrap_or(false)
+    }
+
+    fn resolve_group(&self, _group: &str)-> Option<Vec<String>>{
+        None
+    }
+
+    fn exists(&self, package: &str)-> bool{
+        Command::new("apt-cache")
+            .args(["show", package])
+            .output()
+            .map(|handle| handle.status.success())
+            .unwrap_or(false)
+    }
+
+    fn install(&self, packages: &[String])-> Result<(), AlpsError>{
+        util::run(
+            Command::new("sudo")
+                .args(["apt-get", "install"])
+                .args(packages),
+            "apt"
+        )
+    }
+
+    fn remove(&self, packages: &[String])-> Result<(), AlpsError>{
+        util::run(
+            Command::new("sudo")
+                .args(["apt-get", "remove"])
+                .args(packages),
+            "apt"
+        )
+    }
+
+    fn pinned(&self, package: &str, version: &str)-> Option<String>{
+        Some(package.to_owned() + "=" + version)
+    }
+
+    fn query_version(&self, package: &str, version: &str)-> bool{
+        Command::new("dpkg-query")
+            .args(["-W", "-f=${Status} ${Version}", package])
+            .output()
+            .map(|handle|{
+                handle.status.success()
+                    && String::from_utf8_lossy(&handle.stdout) == "install ok installed ".to_owned() + version
+            })
+            .unwrap_or(false)
+    }
+
+    fn outdated(&self, package: &str)-> bool{
+        let handle = match Command::new("apt-cache").args(["policy", package]).output(){
+            Ok(handle) if handle.status.success() => handle,
+            _ => return false,
+        };
+
+        let policy = String::from_utf8_lossy(&handle.stdout);
+        let version = |field: &str|{
+            policy.lines()
+                .find_map(|line| line.trim().strip_prefix(field))
+                .map(|version| version.trim().to_owned())
+        };
+
+        match (version("Installed:"), version("Candidate:")){
+            (Some(installed), Some(candidate)) => installed != "(none)" && installed != candidate,
+            _ => false,
+        }
+    }
+}
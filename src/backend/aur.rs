@@ -0,0 +1,90 @@
+use std::{
+    env,
+    process::Command,
+};
+use crate::util;
+use crate::error::AlpsError;
+use super::PackageBackend;
+
+pub struct Aur{
+    helper: String,
+}
+
+impl Aur{
+    pub fn new(helper: &str)-> Aur{
+        Aur{ helper: helper.to_owned() }
+    }
+
+    // Helpers refuse to build as root, so drop back to the invoking user when alps itself
+    // was started through sudo. The helper escalates on its own when installing.
+    fn command(&self)-> Command{
+        match env::var("SUDO_USER"){
+            Ok(user) if !user.is_empty() && user != "root" =>{
+                let mut handle = Command::new("sudo");
+                handle.args(["-u", &user, &self.helper]);
+                handle
+            }
+            _ => Command::new(&self.helper),
+        }
+    }
+}
+
+impl PackageBackend for Aur{
+    fn name(&self)-> &str{
+        &self.helper
+    }
+
+    fn available(&self)-> bool{
+        util::in_path(&self.helper)
+    }
+
+    fn query_installed(&self, package: &str)-> bool{
+        Command::new("pacman")
+            .args(["-Q", package])
+            .output()
+            .map(|handle| handle.status.success())
+            .unwrap_or(false)
+    }
+
+    fn resolve_group(&self, _group: &str)-> Option<Vec<String>>{
+        None
+    }
+
+    fn exists(&self, package: &str)-> bool{
+        self.command()
+            .args(["-Si", "--aur", package])
+            .output()
+            .map(|handle| handle.status.success())
+            .unwrap_or(false)
+    }
+
+    fn install(&self, packages: &[String])-> Result<(), AlpsError>{
+        util::run(
+            self.command()
+                .args(["-S", "--aur"])
+                .args(packages),
+            &self.helper
+        )
+    }
+
+    fn remove(&self, packages: &[String])-> Result<(), AlpsError>{
+        util::run(
+            self.command()
+                .arg("-R")
+                .args(packages),
+            &self.helper
+        )
+    }
+
+    fn outdated(&self, package: &str)-> bool{
+        self.command()
+            .arg("-Qua")
+            .output()
+            .map(|handle|{
+                String::from_utf8_lossy(&handle.stdout)
+                    .lines()
+                    .any(|line| line.split_whitespace().next() == Some(package))
+            })
+            .unwrap_or(false)
+    }
+}
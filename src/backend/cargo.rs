@@ -0,0 +1,92 @@
+use std::process::Command;
+use crate::util;
+use crate::error::AlpsError;
+use super::{PackageBackend, split_version};
+
+pub struct Cargo;
+
+impl Cargo{
+    fn installed_version(&self, name: &str)-> Option<String>{
+        let handle = Command::new("cargo")
+            .args(["install", "--list"])
+            .output()
+            .ok()?;
+
+        String::from_utf8_lossy(&handle.stdout)
+            .lines()
+            .filter(|line| !line.starts_with(char::is_whitespace))
+            .find_map(|line|{
+                let mut segments = line.trim_end_matches(':').split_whitespace();
+
+                if segments.next() == Some(name){
+                    segments.next().map(|version| version.trim_start_matches('v').to_owned())
+                }
+                else{
+                    None
+                }
+            })
+    }
+}
+
+impl PackageBackend for Cargo{
+    fn name(&self)-> &str{
+        "cargo"
+    }
+
+    fn available(&self)-> bool{
+        util::in_path("cargo")
+    }
+
+    fn query_installed(&self, package: &str)-> bool{
+        let (name, version) = split_version(package);
+
+        match self.installed_version(name){
+            Some(installed) => version.is_none_or(|version| version == installed),
+            None => false,
+        }
+    }
+
+    fn resolve_group(&self, _group: &str)-> Option<Vec<String>>{
+        None
+    }
+
+    fn exists(&self, package: &str)-> bool{
+        let (name, _) = split_version(package);
+
+        Command::new("cargo")
+            .args(["search", name, "--limit", "1"])
+            .output()
+            .map(|handle|{
+                String::from_utf8_lossy(&handle.stdout)
+                    .lines()
+                    .any(|line| line.starts_with(&(name.to_owned() + " = ")))
+            })
+            .unwrap_or(false)
+    }
+
+    // cargo only takes --version alongside a single crate, so each entry is its own install.
+    fn install(&self, packages: &[String])-> Result<(), AlpsError>{
+        for package in packages{
+            let (name, version) = split_version(package);
+
+            let mut handle = Command::new("cargo");
+            handle.args(["install", name]);
+
+            if let Some(version) = version{
+                handle.args(["--version", version]);
+            }
+
+            util::run(&mut handle, "cargo")?;
+        }
+        Ok(())
+    }
+
+    fn remove(&self, packages: &[String])-> Result<(), AlpsError>{
+        util::run(
+            Command::new("cargo")
+                .arg("uninstall")
+                .args(packages.iter().map(|package| split_version(package).0)),
+            "cargo"
+        )
+    }
+}
@@ -0,0 +1,134 @@
+use std::process::Command;
+use crate::util;
+use crate::error::AlpsError;
+use super::PackageBackend;
+
+pub struct Dnf;
+
+impl PackageBackend for Dnf{
+    fn name(&self)-> &str{
+        "dnf"
+    }
+
+    fn available(&self)-> bool{
+        util::in_path("dnf") && util::in_path("rpm")
+    }
+
+    fn query_installed(&self, package: &str)-> bool{
+        if package.starts_with('@'){
+            return false;
+        }
+
+        Command::new("rpm")
+            .args(["-q", package])
+            .output()
+            .map(|handle| handle.status.success())
+            .unwrap_or(false)
+    }
+
+    // Groups are written as @<group>, the same way dnf install accepts them. Only
+    // mandatory and default members are considered since those are what gets installed.
+    fn resolve_group(&self, group: &str)-> Option<Vec<String>>{
+        let name = group.strip_prefix('@')?;
+
+        let handle = Command::new("dnf")
+            .args(["-q", "group", "info", name])
+            .output()
+            .ok()?;
+
+        if !handle.status.success(){
+            return None;
+        }
+
+        let mut members = Vec::new();
+        let mut section = false;
+
+        for line in String::from_utf8_lossy(&handle.stdout).lines(){
+            match line.split_once(':'){
+                Some((key, value)) =>{
+                    let key = key.trim().to_lowercase();
+                    let value = value.trim();
+
+                    if !key.is_empty(){
+                        section = key == "mandatory packages" || key == "default packages";
+                    }
+
+                    if section && !value.is_empty(){
+                        members.push(value.to_owned());
+                    }
+                }
+                None =>{
+                    let member = line.trim().trim_start_matches(['=', '+', '-']).trim();
+
+                    if section && !member.is_empty(){
+                        members.push(member.to_owned());
+                    }
+                }
+            }
+        }
+
+        if members.is_empty(){
+            None
+        }
+        else{
+            Some(members)
+        }
+    }
+
+    fn exists(&self, package: &str)-> bool{
+        if package.starts_with('@'){
+            return self.resolve_group(package).is_some();
+        }
+
+        Command::new("dnf")
+            .args(["-q", "info", package])
+            .output()
+            .map(|handle| handle.status.success())
+            .unwrap_or(false)
+    }
+
+    fn install(&self, packages: &[String])-> Result<(), AlpsError>{
+        util::run(
+            Command::new("sudo")
+                .args(["dnf", "install"])
+                .args(packages),
+            "dnf"
+        )
+    }
+
+    fn remove(&self, packages: &[String])-> Result<(), AlpsError>{
+        util::run(
+            Command::new("sudo")
+                .args(["dnf", "remove"])
+                .args(packages),
+            "dnf"
+        )
+    }
+
+    fn pinned(&self, package: &str, version: &str)-> Option<String>{
+        Some(package.to_owned() + "-" + version)
+    }
+
+    // Pins name a version, optionally followed by its release, e.g. 14.1.0 or 14.1.0-1.fc40.
+    fn query_version(&self, package: &str, version: &str)-> bool{
+        Command::new("rpm")
+            .args(["-q", "--qf", "%{VERSION}-%{RELEASE}\\n", package])
+            .output()
+            .map(|handle|{
+                handle.status.success()
+                    && String::from_utf8_lossy(&handle.stdout)
+                        .lines()
+                        .any(|installed| installed == version || installed.starts_with(&(version.to_owned() + "-")))
+            })
+            .unwrap_or(false)
+    }
+
+    // check-update exits with 100 when updates are available.
+    fn outdated(&self, package: &str)-> bool{
+        Command::new("dnf")
+            .args(["-q", "check-update", package])
+            .output()
+            .map(|handle| handle.status.code() == Some(100))
+            .unwrap_or(false)
+    }
+}
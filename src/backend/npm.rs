@@ -0,0 +1,76 @@
+use std::process::Command;
+use crate::util;
+use crate::error::AlpsError;
+use super::{PackageBackend, split_version};
+
+pub struct Npm;
+
+impl Npm{
+    fn installed_version(&self, name: &str)-> Option<String>{
+        let handle = Command::new("npm")
+            .args(["ls", "-g", "--depth=0", name])
+            .output()
+            .ok()?;
+
+        if !handle.status.success(){
+            return None;
+        }
+
+        let prefix = name.to_owned() + "@";
+
+        String::from_utf8_lossy(&handle.stdout)
+            .split_whitespace()
+            .find_map(|token| token.strip_prefix(&prefix).map(|version| version.to_owned()))
+    }
+}
+
+impl PackageBackend for Npm{
+    fn name(&self)-> &str{
+        "npm"
+    }
+
+    fn available(&self)-> bool{
+        util::in_path("npm")
+    }
+
+    fn query_installed(&self, package: &str)-> bool{
+        let (name, version) = split_version(package);
+
+        match self.installed_version(name){
+            Some(installed) => version.is_none_or(|version| version == installed),
+            None => false,
+        }
+    }
+
+    fn resolve_group(&self, _group: &str)-> Option<Vec<String>>{
+        None
+    }
+
+    fn exists(&self, package: &str)-> bool{
+        Command::new("npm")
+            .args(["view", package, "version"])
+            .output()
+            .map(|handle|{
+                handle.status.success() && !String::from_utf8_lossy(&handle.stdout).trim().is_empty()
+            })
+            .unwrap_or(false)
+    }
+
+    fn install(&self, packages: &[String])-> Result<(), AlpsError>{
+        util::run(
+            Command::new("npm")
+                .args(["install", "-g"])
+                .args(packages),
+            "npm"
+        )
+    }
+
+    fn remove(&self, packages: &[String])-> Result<(), AlpsError>{
+        util::run(
+            Command::new("npm")
+                .args(["uninstall", "-g"])
+                .args(packages.iter().map(|package| split_version(package).0)),
+            "npm"
+        )
+    }
+}
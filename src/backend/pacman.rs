@@ -0,0 +1,79 @@
+use std::process::Command;
+use crate::util;
+use crate::error::AlpsError;
+use super::PackageBackend;
+
+pub struct Pacman;
+
+impl PackageBackend for Pacman{
+    fn name(&self)-> &str{
+        "pacman"
+    }
+
+    fn available(&self)-> bool{
+        util::in_path("pacman")
+    }
+
+    fn query_installed(&self, package: &str)-> bool{
+        Command::new("pacman")
+            .args(["-Q", package])
+            .output()
+            .map(|handle| handle.status.success())
+            .unwrap_or(false)
+    }
+
+    fn resolve_group(&self, group: &str)-> Option<Vec<String>>{
+        let handle = Command::new("pacman")
+            .args(["-Sg", group])
+            .output()
+            .ok()?;
+
+        if handle.status.success(){
+            Some(
+                String::from_utf8_lossy(&handle.stdout)
+                    .split_whitespace()
+                    .filter(|entry| *entry != group)
+                    .map(|entry| entry.to_owned())
+                    .collect()
+            )
+        }
+        else{
+            None
+        }
+    }
+
+    fn exists(&self, package: &str)-> bool{
+        Command::new("pacman")
+            .args(["-Ss", &("^".to_owned() + package + "$")])
+            .output()
+            .map(|handle| handle.status.success())
+            .unwrap_or(false)
+    }
+
+    fn install(&self, packages: &[String])-> Result<(), AlpsError>{
+        util::run(
+            Command::new("sudo")
+                .args(["pacman", "-S"])
+                .args(packages),
+            "pacman"
+        )
+    }
+
+    fn remove(&self, packages: &[String])-> Result<(), AlpsError>{
+        util::run(
+            Command::new("sudo")
+                .args(["pacman", "-R"])
+                .args(packages),
+            "pacman"
+        )
+    }
+
+    // Checked against the last synced databases.
+    fn outdated(&self, package: &str)-> bool{
+        Command::new("pacman")
+            .args(["-Qu", package])
+            .output()
+            .map(|handle| handle.status.success())
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,98 @@
+use std::process::Command;
+use crate::util;
+use crate::error::AlpsError;
+use super::{PackageBackend, split_version};
+
+pub struct Pipx;
+
+impl Pipx{
+    fn installed_version(&self, name: &str)-> Option<String>{
+        let handle = Command::new("pipx")
+            .args(["list", "--short"])
+            .output()
+            .ok()?;
+
+        String::from_utf8_lossy(&handle.stdout)
+            .lines()
+            .find_map(|line|{
+                let (package, version) = line.trim().split_once(' ')?;
+
+                if package.eq_ignore_ascii_case(name){
+                    Some(version.trim().to_owned())
+                }
+                else{
+                    None
+                }
+            })
+    }
+}
+
+impl PackageBackend for Pipx{
+    fn name(&self)-> &str{
+        "pipx"
+    }
+
+    fn available(&self)-> bool{
+        util::in_path("pipx")
+    }
+
+    fn query_installed(&self, package: &str)-> bool{
+        let (name, version) = split_version(package);
+
+        match self.installed_version(name){
+            Some(installed) => version.is_none_or(|version| version == installed),
+            None => false,
+        }
+    }
+
+    fn resolve_group(&self, _group: &str)-> Option<Vec<String>>{
+        None
+    }
+
+    fn exists(&self, package: &str)-> bool{
+        let (name, version) = split_version(package);
+
+        Command::new("python3")
+            .args(["-m", "pip", "index", "versions", name])
+            .output()
+            .map(|handle|{
+                let stdout = String::from_utf8_lossy(&handle.stdout);
+
+                handle.status.success()
+                    && version.is_none_or(|version|{
+                        stdout.lines()
+                            .filter_map(|line| line.strip_prefix("Available versions:"))
+                            .any(|versions| versions.split(',').any(|entry| entry.trim() == version))
+                    })
+            })
+            .unwrap_or(false)
+    }
+
+    // --force lets a pinned entry replace whatever version is already installed.
+    fn install(&self, packages: &[String])-> Result<(), AlpsError>{
+        for package in packages{
+            let spec = match split_version(package){
+                (name, Some(version)) => name.to_owned() + "==" + version,
+                (name, None) => name.to_owned(),
+            };
+
+            util::run(
+                Command::new("pipx")
+                    .args(["install", "--force", &spec]),
+                "pipx"
+            )?;
+        }
+        Ok(())
+    }
+
+    fn remove(&self, packages: &[String])-> Result<(), AlpsError>{
+        for package in packages{
+            util::run(
+                Command::new("pipx")
+                    .args(["uninstall", split_version(package).0]),
+                "pipx"
+            )?;
+        }
+        Ok(())
+    }
+}
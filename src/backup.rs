@@ -0,0 +1,236 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use serde::{Serialize, Deserialize};
+use crate::util;
+use crate::attributes;
+use crate::ignore::Ignore;
+use crate::error::AlpsError;
+
+// Snapshots live under <state_dir>/snapshots/<snapshot>/, the previous contents of every
+// file mirrored below files/ by their absolute path. They hold decrypted secrets, so they
+// never go in the shared store.
+pub const DIR: &str = "snapshots";
+// Where older versions kept them, inside the store.
+pub const LEGACY_DIR: &str = ".backups";
+const RECORD: &str = "snapshot.toml";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Record{
+    group: String,
+    // Files that were overwritten, restored on rollback.
+    #[serde(default)]
+    saved: Vec<String>,
+    // Files that didn't exist before, removed on rollback.
+    #[serde(default)]
+    created: Vec<String>,
+    // Paths cleared to make way for a config, e.g. a directory replaced by a symlink.
+    // Whatever sits there now is removed on rollback before restoring their files.
+    #[serde(default)]
+    replaced: Vec<String>,
+    // Symlinks that were removed, restored on rollback.
+    #[serde(default)]
+    links: Vec<Link>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Link{
+    path: String,
+    target: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Restore{
+    Restored,
+    Removed,
+    Failed(String),
+}
+
+pub struct Snapshot{
+    name: String,
+    path: String,
+    record: Record,
+}
+
+impl Snapshot{
+    // Nothing is written until the first file is saved, so syncs that change nothing
+    // leave no snapshot behind.
+    pub(crate) fn new(state_dir: &str, group: &str)-> Snapshot{
+        let stamp = util::timestamp() + "-" + group;
+        let mut name = stamp.to_owned();
+        let mut count = 1;
+
+        while Path::new(&(state_dir.to_owned() + DIR + "/" + &name)).exists(){
+            count += 1;
+            name = stamp.to_owned() + "-" + &count.to_string();
+        }
+
+        Snapshot{
+            path: state_dir.to_owned() + DIR + "/" + &name,
+            name,
+            record: Record{ group: group.to_owned(), ..Record::default() },
+        }
+    }
+
+    pub(crate) fn open(state_dir: &str, name: &str)-> Result<Snapshot, AlpsError>{
+        let path = state_dir.to_owned() + DIR + "/" + name;
+
+        let text = match fs::read_to_string(path.to_owned() + "/" + RECORD){
+            Ok(text) => text,
+            Err(_) => return Err(AlpsError::invalid("snapshot", name)),
+        };
+        let record = toml::from_str(&text)
+            .map_err(|error| AlpsError::invalid("snapshot", &(name.to_owned() + ": " + error.message())))?;
+
+        Ok(Snapshot{ name: name.to_owned(), path, record })
+    }
+
+    pub fn name(&self)-> &str{
+        &self.name
+    }
+
+    pub fn group(&self)-> &str{
+        &self.record.group
+    }
+
+    pub fn saved(&self)-> &[String]{
+        &self.record.saved
+    }
+
+    pub fn created(&self)-> &[String]{
+        &self.record.created
+    }
+
+    fn file(&self, target: &Path)-> PathBuf{
+        Path::new(&(self.path.to_owned() + "/files")).join(target.strip_prefix("/").unwrap_or(target))
+    }
+
+    // Records target before it gets written. Files already saved by this snapshot keep
+    // their first contents.
+    pub(crate) fn save(&mut self, target: &Path)-> Result<(), AlpsError>{
+        let entry = target.display().to_string();
+
+        if self.record.saved.contains(&entry) || self.record.created.contains(&entry){
+            return Ok(());
+        }
+
+        if target.is_file(){
+            util::copy_dir(target, self.file(target))?;
+            self.record.saved.push(entry);
+        }
+        else{
+            self.record.created.push(entry);
+        }
+        self.write()
+    }
+
+    // Records whatever is at target, file, directory or symlink, then removes it.
+    pub(crate) fn replace(&mut self, target: &Path)-> Result<(), AlpsError>{
+        let entry = target.display().to_string();
+
+        if let Ok(link) = fs::read_link(target){
+            self.record.links.push(Link{ path: entry.to_owned(), target: link.display().to_string() });
+            self.record.replaced.push(entry);
+            self.write()?;
+
+            fs::remove_file(target)?;
+        }
+        else if target.is_dir(){
+            for (file, _) in util::copy_targets(target, target)?{
+                self.save(&file)?;
+            }
+            // Left out by copy_targets, so kept as links.
+            for entry in attributes::record(target, &Ignore::default())?{
+                if let Some(link) = entry.link{
+                    self.record.links.push(Link{ path: target.join(entry.path).display().to_string(), target: link });
+                }
+            }
+            self.record.replaced.push(entry);
+            self.write()?;
+
+            fs::remove_dir_all(target)?;
+        }
+        else if target.exists(){
+            self.save(target)?;
+            self.record.replaced.push(entry);
+            self.write()?;
+
+            fs::remove_file(target)?;
+        }
+        Ok(())
+    }
+
+    fn write(&self)-> Result<(), AlpsError>{
+        fs::create_dir_all(&self.path)?;
+
+        let text = toml::to_string_pretty(&self.record)
+            .map_err(|error| AlpsError::invalid("snapshot", &error.to_string()))?;
+        fs::write(self.path.to_owned() + "/" + RECORD, text)?;
+
+        Ok(())
+    }
+
+    // Removes what the sync created, then puts every saved file and symlink back.
+    pub fn rollback(&self)-> Vec<(String, Restore)>{
+        let mut restored = Vec::new();
+
+        for entry in &self.record.created{
+            let restore = match fs::remove_file(entry){
+                Ok(()) => Restore::Removed,
+                Err(error) => Restore::Failed(error.to_string()),
+            };
+            restored.push((entry.to_owned(), restore));
+        }
+
+        for entry in &self.record.replaced{
+            let path = Path::new(entry);
+            let _ = match path.symlink_metadata(){
+                Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+                Ok(_) => fs::remove_file(path),
+                Err(_) => Ok(()),
+            };
+        }
+
+        for link in &self.record.links{
+            if let Some(parent) = Path::new(&link.path).parent(){
+                let _ = fs::create_dir_all(parent);
+            }
+
+            let restore = match std::os::unix::fs::symlink(&link.target, &link.path){
+                Ok(()) => Restore::Restored,
+                Err(error) => Restore::Failed(error.to_string()),
+            };
+            restored.push((link.path.to_owned(), restore));
+        }
+
+        for entry in &self.record.saved{
+            let restore = match util::copy_dir(self.file(Path::new(entry)), entry){
+                Ok(()) => Restore::Restored,
+                Err(error) => Restore::Failed(error.to_string()),
+            };
+            restored.push((entry.to_owned(), restore));
+        }
+        restored
+    }
+}
+
+// Snapshot names, oldest first.
+pub(crate) fn list(state_dir: &str)-> Result<Vec<String>, AlpsError>{
+    let mut snapshots = Vec::new();
+
+    let entries = match fs::read_dir(state_dir.to_owned() + DIR){
+        Ok(entries) => entries,
+        Err(_) => return Ok(snapshots),
+    };
+
+    for entry in entries{
+        let entry = entry?;
+        if entry.path().join(RECORD).is_file(){
+            snapshots.push(entry.file_name().to_string_lossy().into_owned());
+        }
+    }
+    snapshots.sort();
+
+    Ok(snapshots)
+}
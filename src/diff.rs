@@ -0,0 +1,140 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use similar::TextDiff;
+use crate::util;
+use crate::group::Group;
+use crate::manifest::Config;
+use crate::template::Vars;
+use crate::sync;
+use crate::secret;
+use crate::ignore::Ignore;
+use crate::error::AlpsError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change{
+    Unchanged,
+    // Missing on the system, syncing creates it.
+    Created,
+    // Unified diff from the live file to the one stored in the group.
+    Text(String),
+    Binary,
+    // Something other than a file is in the way, e.g. a directory.
+    Conflict,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileDiff{
+    pub source: PathBuf,
+    pub target: PathBuf,
+    pub change: Change,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigDiff{
+    // The entry as written in the group.
+    pub entry: String,
+    pub source: PathBuf,
+    // The destination after to_userdir.
+    pub target: PathBuf,
+    // Every file syncing would write, empty when the stored copy is gone.
+    pub files: Vec<FileDiff>,
+    // Why the config couldn't be compared, e.g. a secret that doesn't decrypt.
+    pub error: Option<String>,
+    // A secret config left encrypted, since diffs don't ask for the passphrase.
+    pub locked: bool,
+}
+
+impl ConfigDiff{
+    pub fn is_dir(&self)-> bool{
+        self.source.is_dir()
+    }
+
+    pub fn changed(&self)-> bool{
+        self.files.iter().any(|file| file.change != Change::Unchanged)
+    }
+
+    pub fn count(&self, change: fn(&Change)-> bool)-> usize{
+        self.files.iter().filter(|file| change(&file.change)).count()
+    }
+}
+
+pub(crate) fn config(group: &Group, config: &Config)-> Result<ConfigDiff, AlpsError>{
+    let mut target = config.target.to_owned();
+    util::to_userdir(&mut target)?;
+
+    let source = PathBuf::from(group.path() + "/configs/" + &config.source);
+
+    let locked = config.secret && !secret::unlocked();
+
+    // One config that can't be compared doesn't keep the others from being diffed.
+    let (files, error) = if locked{
+        (Vec::new(), None)
+    }
+    else{
+        match files(group, config, &source, Path::new(&target)){
+            Ok(files) => (files, None),
+            Err(error) => (Vec::new(), Some(error.to_string())),
+        }
+    };
+
+    Ok(ConfigDiff{ entry: config.entry(), source, target: PathBuf::from(target), files, error, locked })
+}
+
+fn files(group: &Group, config: &Config, source: &Path, target: &Path)-> Result<Vec<FileDiff>, AlpsError>{
+    let mut files = Vec::new();
+
+    // Templated configs are compared as they would be rendered on this host, secret ones
+    // once decrypted.
+    let vars = if config.template{
+        Some(Vars::load(group.state_dir())?)
+    }
+    else{
+        None
+    };
+
+    let ignore = Ignore::new(&group.manifest()?, Some(config))?;
+
+    for (source, target) in ignore.targets(source, target)?{
+        let stored = sync::contents(&source, vars.as_ref(), config.secret)?;
+        let change = file(&stored, &source, &target)?;
+        files.push(FileDiff{ source, target, change });
+    }
+    Ok(files)
+}
+
+fn file(stored: &[u8], source: &Path, target: &Path)-> Result<Change, AlpsError>{
+    if !target.exists(){
+        return Ok(Change::Created);
+    }
+    else if !target.is_file(){
+        return Ok(Change::Conflict);
+    }
+
+    let live = fs::read(target)?;
+
+    if stored == live{
+        return Ok(Change::Unchanged);
+    }
+
+    match (text(&live), text(stored)){
+        (Some(live), Some(stored)) =>{
+            let diff = TextDiff::from_lines(live, stored)
+                .unified_diff()
+                .header(&target.display().to_string(), &source.display().to_string())
+                .to_string();
+
+            Ok(Change::Text(diff))
+        }
+        _ => Ok(Change::Binary),
+    }
+}
+
+// Contents read as text, unless they look binary.
+fn text(contents: &[u8])-> Option<&str>{
+    if contents.contains(&0){
+        return None;
+    }
+    std::str::from_utf8(contents).ok()
+}
@@ -0,0 +1,64 @@
+use std::{
+    fmt,
+    io,
+};
+
+#[derive(Debug)]
+pub enum AlpsError{
+    Usage(String),
+    MissingGroup(String),
+    InvalidEntry{ kind: String, entry: String },
+    Io(io::Error),
+    Backend{ command: String, reason: String },
+    PermissionDenied(String),
+    Failed(usize),
+}
+
+impl AlpsError{
+    pub fn invalid(kind: &str, entry: &str)-> AlpsError{
+        AlpsError::InvalidEntry{ kind: kind.to_owned(), entry: entry.to_owned() }
+    }
+
+    pub fn backend(command: &str, reason: impl ToString)-> AlpsError{
+        AlpsError::Backend{ command: command.to_owned(), reason: reason.to_string() }
+    }
+
+    // Failed carries how many entries went wrong, which is what queries have always
+    // exited with. Clamped so a multiple of 256 can't wrap around to success.
+    pub fn exit_code(&self)-> i32{
+        match self{
+            AlpsError::Failed(count) => (*count).clamp(1, 255) as i32,
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for AlpsError{
+    fn fmt(&self, f: &mut fmt::Formatter)-> fmt::Result{
+        match self{
+            AlpsError::Usage(message) => write!(f, "{message}"),
+            AlpsError::MissingGroup(group) if group.is_empty() => write!(f, "Expected group! (use -h for help)"),
+            AlpsError::MissingGroup(group) => write!(f, "Invalid group ({group})! (use -h for help)"),
+            AlpsError::InvalidEntry{ kind, entry } => write!(f, "Invalid {kind} ({entry})!"),
+            AlpsError::Io(error) => write!(f, "IO error ({error})!"),
+            AlpsError::Backend{ command, reason } => write!(f, "Backend ({command}) failed: {reason}!"),
+            AlpsError::PermissionDenied(path) => write!(f, "Permission denied for ({path})!"),
+            AlpsError::Failed(count) => write!(f, "({count}) entries failed!"),
+        }
+    }
+}
+
+impl std::error::Error for AlpsError{
+    fn source(&self)-> Option<&(dyn std::error::Error + 'static)>{
+        match self{
+            AlpsError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for AlpsError{
+    fn from(error: io::Error)-> AlpsError{
+        AlpsError::Io(error)
+    }
+}
@@ -1,28 +1,54 @@
 use crate::sift;
-use crate::util;
+use alps::{AlpsError, Store, Group, Manifest, Phase, SyncOptions, SyncReport, SyncEntry, Outcome, Change, ConfigDiff, Restore, Status};
+use alps::backend;
+use alps::manifest::{Mode, Config, Hook, Package};
+use alps::flatpak::{self, Flatpak};
+use alps::secret;
 use std::{
-    fs,
+    fs::{self, OpenOptions},
+    env,
     path::Path,
-    process::{Command, Stdio},
-    io::ErrorKind,
+    process::{self, Command},
+    io::Write,
+    os::unix::fs::OpenOptionsExt,
 };
 use colored::Colorize;
 
+pub fn help_menu(){
+    println!("{} alps <operation> [...]", "usage:".white());
+    println!("{}", "operations:".white());
+    println!("   alps {{-Q --query}} [flags] : query installed groups and their contents in your config");
+    println!("   alps {{-S --sync}} [flags] : sync your system with a group and their contents");
+    println!("   alps {{-I --install}} [flags] : install a group and their contents in your config");
+    println!("   alps {{-R --remove}} [flags] : remove a group and their contents in your config");
+    println!("   alps {{-E --edit}} [flags] : edit a group and their contents in your config");
+    println!("   alps {{-D --diff}} [flags] : diff a group's configs against your system");
+    println!("   alps {{-U --update}} [flags] : update a group's contents from your system");
+    println!("   alps {{--rollback}} [?snapshot] : restore files overwritten by a config sync");
+    println!("{} use {{-h --help}} on any operation for list of flags", "hint:".white())
+}
+
 pub fn install_help(){
     println!("{} {{-I}} [flag] [...]", "usage:".white());
     println!("{}", "flags:".white());
     println!("   {{-g --group}} [group(s)] : install group to config");
-    println!("   {{-c --config}} [group] [config(s)] : install file to group");
-    println!("   {{-p --package}} [group] [package(s)] : install package to group");
+    println!("   {{-c --config}} [group] [config(s)] : install file to group, {{--symlink}} to deploy it as a symlink");
+    println!("      {{--template}} : render {{{{ variables }}}} in the file when syncing");
+    println!("      {{--secret}} : store the file encrypted with a passphrase (or ${})", secret::VAR);
+    println!("   {{-p --package}} [group] [package(s)] : install package ([backend:]name[@version]) to group");
     println!("   {{-s --script}} [group] [script(s)] : install script to group");
+    println!("   {{-f --flatpak}} [group] [flatpak(s)] : install flatpak ([user:]remote:app-id) or remote (remote=url) to group");
+    println!("   {{-t --tool}} [group] [tool(s)] : install cargo, pipx or npm tool (tool:name[@version]) to group");
+    println!("   {{-b --backend}} [group] [backend] : set package manager of group");
+    println!("   {{-i --include}} [group] [group(s)] : include other groups, synced along with the group");
 }
 
-pub fn install_group(mut args: Vec<String>, home_dir: &str){
-    sift::missing_args(&mut args, 1);
-    sift::invalid_groups(home_dir, &mut args, true);
+pub fn install_group(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    sift::missing_args(&args, 1)?;
+    sift::invalid_groups(store.home_dir(), &mut args, true);
 
     for arg in args{
-        if fs::create_dir(home_dir.to_owned() + &arg).is_ok(){
+        if store.create_group(&arg).is_ok(){
             println!(
                 "{} Created group ({})...",
                 "[+]".green(),
@@ -30,113 +56,183 @@ pub fn install_group(mut args: Vec<String>, home_dir: &str){
             );
         }
     }
+    Ok(())
 }
 
-pub fn install_package(mut args: Vec<String>, home_dir: &str){
-    let mut group = String::new();
-
-    sift::missing_group(home_dir, &mut args, &mut group);
-    sift::missing_args(&mut args, 1);
-    sift::invalid_packages(home_dir, &mut args, true, &group);
+pub fn install_package(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
+    sift::invalid_packages(store.home_dir(), &mut args, true, group.name())?;
 
     for arg in args{
-        util::config_write(&group, "[PACKAGES]", &arg, home_dir, true);
+        group.add_package(&arg)?;
 
         println!(
             "{} Installed {}/{}/{}",
             "[+]".green(),
-            group.green(),
+            group.name().green(),
             "packages".green(),
             arg.green()
         );
     }
-
+    Ok(())
 }
 
-pub fn install_config(mut args: Vec<String>, home_dir: &str){
-    let mut group = String::new();
+pub fn install_config(mut args: Vec<String>, store: &Store, symlink: bool, template: bool, secret: bool)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
+    sift::invalid_configs(store.home_dir(), &mut args, true, group.name())?;
 
-    sift::missing_group(home_dir, &mut args, &mut group);
-    sift::missing_args(&mut args, 1);
-    sift::invalid_configs(home_dir, &mut args, true, &group);
+    for arg in &args{
+        group.add_config(arg, symlink.then_some(Mode::Symlink), template, secret)?;
 
-    fs::create_dir(home_dir.to_owned() + &group + "/configs");
+        let config_name = arg.split('/').next_back().unwrap();
 
-    for arg in &mut args{
-        let mut config = arg.to_owned();
-        util::to_template(&mut config);
+        println!(
+            "{} Installed {}/{}/{}",
+            "[+]".green(),
+            group.name().green(),
+            "configs".green(),
+            config_name.green()
+        );
+    }
+    Ok(())
+}
 
-        let (_, config_name) = arg.rsplit_once('/').unwrap();
-        let postfix = "_".to_owned()
-            + &(util::dup_count(config_name, &group, home_dir)+1).to_string();
-        config = config + &postfix;
+pub fn install_script(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
+    sift::invalid_scripts(store.home_dir(), &mut args, true, group.name())?;
 
-        util::copy_dir(
-            &arg, 
-            home_dir.to_owned()
-                + &group
-                + "/configs/"
-                + config_name
-                + &postfix
-        );
-        util::config_write(&group, "[CONFIGS]", &config, home_dir, true);
+    for arg in &args{
+        let script_name = group.add_script(arg)?;
 
         println!(
             "{} Installed {}/{}/{}",
             "[+]".green(),
-            group.green(),
-            "configs".green(),
-            config_name.green()
+            group.name().green(),
+            "scripts".green(),
+            script_name.green()
         );
     }
+    Ok(())
 }
 
-pub fn install_script(mut args: Vec<String>, home_dir: &str){
-    let mut group = String::new();
+pub fn install_flatpak(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
+    sift::invalid_flatpaks(store.home_dir(), &mut args, true, group.name())?;
+
+    for arg in &args{
+        if let Some((remote, url)) = arg.split_once('='){
+            group.add_flatpak_remote(remote, url)?;
+
+            println!(
+                "{} Installed {}/{}/{}",
+                "[+]".green(),
+                group.name().green(),
+                "flatpak remotes".green(),
+                remote.green()
+            );
+        }
+        else{
+            group.add_flatpak(arg)?;
+
+            println!(
+                "{} Installed {}/{}/{}",
+                "[+]".green(),
+                group.name().green(),
+                "flatpaks".green(),
+                arg.green()
+            );
+        }
+    }
+    Ok(())
+}
 
-    sift::missing_group(home_dir, &mut args, &mut group);
-    sift::missing_args(&mut args, 1);
-    sift::invalid_scripts(home_dir, &mut args, true, &group);
+pub fn install_tool(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
+    sift::invalid_tools(store.home_dir(), &mut args, true, group.name())?;
 
-    fs::create_dir(home_dir.to_owned() + &group + "/scripts");
-    
     for arg in &args{
-        let arg_name = arg.split('/').last().unwrap();
-        util::config_write(&group, "[SCRIPTS]", arg_name, home_dir, true);
-
-        fs::copy(
-            &arg, 
-            home_dir.to_owned()
-                + &group
-                + "/scripts/"
-                + arg_name
+        let (tool, spec) = arg.split_once(':').unwrap();
+        group.add_tool(tool, spec)?;
+
+        println!(
+            "{} Installed {}/{}/{}",
+            "[+]".green(),
+            group.name().green(),
+            tool.green(),
+            spec.green()
         );
+    }
+    Ok(())
+}
+
+pub fn install_backend(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
+    sift::invalid_backend(&args[0])?;
+
+    group.set_backend(&args[0])?;
+
+    println!(
+        "{} Installed {}/{}/{}",
+        "[+]".green(),
+        group.name().green(),
+        "backend".green(),
+        args[0].green()
+    );
+    Ok(())
+}
+
+pub fn install_include(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
+
+    for arg in &args{
+        if let Err(error) = group.add_include(arg){
+            eprintln!(
+                "{} Include ({}) failed: {}",
+                "[!]".yellow(),
+                arg.yellow(),
+                error
+            );
+            continue;
+        }
 
         println!(
             "{} Installed {}/{}/{}",
             "[+]".green(),
-            group.green(),
-            "scripts".green(),
-            arg_name.green()
+            group.name().green(),
+            "includes".green(),
+            arg.green()
         );
     }
+    Ok(())
 }
 
 pub fn remove_help(){
     println!("{} {{-R}} [flag]", "usage:".white());
     println!("{}", "flags:".white());
     println!("   {{-g --group}} [group(s)] : remove specified groups including contents");
-    println!("   {{-c --config}} [group] [config(s)] : remove specified config(s) of group");       
+    println!("   {{-c --config}} [group] [config(s)] : remove specified config(s) of group");
     println!("   {{-p --package}} [group] [package(s)] : remove specified package(s) of group");
-    println!("   {{-s --script}} [group] [script(s)] : remove specified script(s) of group");       
+    println!("   {{-s --script}} [group] [script(s)] : remove specified script(s) of group");
+    println!("   {{-f --flatpak}} [group] [flatpak(s)] : remove specified flatpak(s) of group");
+    println!("   {{-t --tool}} [group] [tool(s)] : remove specified tool(s) of group");
+    println!("   {{-b --backend}} [group] : unset package manager of group");
+    println!("   {{-i --include}} [group] [group(s)] : stop including group(s) in group");
+    println!("   {{-u --uninstall}} : with -p, -f or -t, also uninstall entries from system");
 }
 
-pub fn remove_group(mut args: Vec<String>, home_dir: &str){
-    sift::missing_args(&mut args, 1);
-    sift::invalid_groups(home_dir, &mut args, false);
+pub fn remove_group(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    sift::missing_args(&args, 1)?;
+    sift::invalid_groups(store.home_dir(), &mut args, false);
 
     for arg in args{
-        if fs::remove_dir_all(home_dir.to_owned() + &arg).is_ok(){
+        if store.remove_group(&arg).is_ok(){
             println!(
                 "{} Removed group ({})...",
                 "[-]".green(),
@@ -144,415 +240,470 @@ pub fn remove_group(mut args: Vec<String>, home_dir: &str){
             );
         }
     }
+    Ok(())
 }
 
-pub fn remove_package(mut args: Vec<String>, home_dir: &str){
-    let mut group = String::new();
-
-    sift::missing_group(home_dir, &mut args, &mut group);
-    sift::missing_args(&mut args, 1);
-    sift::invalid_packages(home_dir, &mut args, false, &group);
+pub fn remove_package(mut args: Vec<String>, store: &Store, uninstall: bool)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
+    sift::invalid_packages(store.home_dir(), &mut args, false, group.name())?;
 
     for arg in &args{
-        util::config_write(&group, "[PACKAGES]", arg, home_dir, false);
+        group.remove_package(arg)?;
 
         println!(
             "{} Removed {}/{}/{}...",
             "[-]".green(),
-            group.green(),
+            group.name().green(),
             "packages".green(),
             arg.green()
         );
     }
-}
 
-pub fn remove_config(mut args: Vec<String>, home_dir: &str){
-    let mut group = String::new();
-
-    sift::missing_group(home_dir, &mut args, &mut group);
-    sift::missing_args(&mut args, 1);
-    sift::invalid_configs(home_dir, &mut args, false, &group);
+    if uninstall && !args.is_empty(){
+        let backend = group.backend()?;
+        // Entries limited to another package manager were never installed here.
+        let packages = args.iter()
+            .map(|package| Package::parse(package))
+            .filter(|package| package.backend.as_deref().is_none_or(|name| name == "aur" || name == backend.name()))
+            .map(|package| package.name)
+            .collect::<Vec<String>>();
+
+        if !packages.is_empty(){
+            backend.remove(&packages)?;
+        }
+    }
+    Ok(())
+}
 
+pub fn remove_config(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
+    sift::invalid_configs(store.home_dir(), &mut args, false, group.name())?;
 
     for arg in &args{
-        util::config_write(&group, "[CONFIGS]", arg, home_dir, false);
-
-        let config_name = arg.split('/').last().unwrap();
-        let config_path = home_dir.to_owned() + &group + "/configs/" + config_name;
+        group.remove_config(arg)?;
 
-        if Path::new(&config_path).is_dir(){
-            fs::remove_dir_all(config_path);
-        }
-        else{
-            fs::remove_file(config_path);
-        }
+        let config_name = arg.split('/').next_back().unwrap();
 
         println!(
             "{} Removed {}/{}/{}...",
             "[-]".green(),
-            group.green(),
+            group.name().green(),
             "configs".green(),
             config_name.green()
         );
     }
+    Ok(())
 }
 
-pub fn remove_script(mut args: Vec<String>, home_dir: &str){
-    let mut group = String::new();
-
-    sift::missing_group(home_dir, &mut args, &mut group);
-    sift::missing_args(&mut args, 1);
-    sift::invalid_scripts(home_dir, &mut args, false, &group);
+pub fn remove_script(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
+    sift::invalid_scripts(store.home_dir(), &mut args, false, group.name())?;
 
-        for arg in &args{
-        util::config_write(&group, "[SCRIPTS]", arg, home_dir, false);
-        fs::remove_file(home_dir.to_owned() + &group + "/scripts/" + arg);
+    for arg in &args{
+        group.remove_script(arg)?;
 
         println!(
             "{} Removed {}/{}/{}...",
             "[-]".green(),
-            group.green(),
+            group.name().green(),
             "scripts".green(),
             arg.green()
         );
-    } 
+    }
+    Ok(())
+}
+
+pub fn remove_flatpak(mut args: Vec<String>, store: &Store, uninstall: bool)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
+    sift::invalid_flatpaks(store.home_dir(), &mut args, false, group.name())?;
+
+    for arg in &args{
+        group.remove_flatpak(arg)?;
+
+        println!(
+            "{} Removed {}/{}/{}...",
+            "[-]".green(),
+            group.name().green(),
+            "flatpaks".green(),
+            arg.green()
+        );
+    }
+
+    if uninstall{
+        for user in [false, true]{
+            let apps = args.iter()
+                .filter_map(|arg| Flatpak::parse(arg))
+                .filter(|entry| entry.user == user && entry.installed())
+                .map(|entry| entry.app.to_owned())
+                .collect::<Vec<String>>();
+
+            if !apps.is_empty(){
+                flatpak::uninstall(user, &apps)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn remove_tool(mut args: Vec<String>, store: &Store, uninstall: bool)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
+    sift::invalid_tools(store.home_dir(), &mut args, false, group.name())?;
+
+    for (tool, _) in backend::TOOLS{
+        let specs = args.iter()
+            .filter_map(|arg| arg.split_once(':'))
+            .filter(|(kind, _)| *kind == tool)
+            .map(|(_, spec)| spec.to_owned())
+            .collect::<Vec<String>>();
+
+        for spec in &specs{
+            group.remove_tool(tool, spec)?;
+
+            println!(
+                "{} Removed {}/{}/{}...",
+                "[-]".green(),
+                group.name().green(),
+                tool.green(),
+                spec.green()
+            );
+        }
+
+        if uninstall && !specs.is_empty(){
+            if let Some(backend) = backend::from_tool(tool){
+                backend.remove(&specs)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn remove_backend(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    let entries = group.unset_backend()?;
+
+    if !entries.is_empty(){
+        for entry in entries{
+            println!(
+                "{} Removed {}/{}/{}...",
+                "[-]".green(),
+                group.name().green(),
+                "backend".green(),
+                entry.green()
+            );
+        }
+    }
+    else{
+        eprintln!(
+            "{} No backend set for group ({})!",
+            "[!]".yellow(),
+            group.name().yellow()
+        );
+    }
+    Ok(())
+}
+
+pub fn remove_include(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
+    let includes = group.includes()?;
+
+    for arg in &args{
+        if includes.contains(arg){
+            group.remove_include(arg)?;
+
+            println!(
+                "{} Removed {}/{}/{}...",
+                "[-]".green(),
+                group.name().green(),
+                "includes".green(),
+                arg.green()
+            );
+        }
+        else{
+            eprintln!(
+                "{} {}/{}/{} does not exist!",
+                "[!]".yellow(),
+                group.name().yellow(),
+                "includes".yellow(),
+                arg.yellow()
+            );
+        }
+    }
+    Ok(())
 }
 
 pub fn sync_help(){
     println!("{} {{-S}} [flag]", "usage:".white());
     println!("{}", "flags:".white());
     println!("   {{-g --group}} [group] : sync system with all group contents");
-    println!("   {{-c --config}} [group] : sync system with only group configs");       
+    println!("   {{-c --config}} [group] : sync system with only group configs");
     println!("   {{-p --package}} [group] : sync system with only group packages");
-    println!("   {{-s --script}} [group] : sync system with only group scripts"); 
+    println!("   {{-s --script}} [group] : sync system with only group scripts");
+    println!("   {{-f --flatpak}} [group] : sync system with only group flatpaks");
+    println!("   {{-t --tool}} [group] : sync system with only group tools");
+    println!("   {{--dry-run}} : print what would be synced without touching the system");
 }
 
-pub fn sync_group(home_dir: &str, group: &str){
-    sync_package(home_dir, group);
-    sync_config(home_dir, group);
-    sync_script(home_dir, group);
+// One sync of every phase, so a script aborting the sync stops the phases after it.
+pub fn sync_group(group: &Group, dry_run: bool)-> Result<(), AlpsError>{
+    let report = group.sync(&SyncOptions::default().dry_run(dry_run))?;
+    print_report(group, &report, &Phase::ALL, dry_run)
 }
 
-pub fn sync_package(home_dir: &str, group: &str){
-    println!(
-        "{} Syncing packages of group ({}) {}",
-        "=====".purple(),
-        group.purple(),
-        "=====".purple()
-    );
+pub fn sync_phase(group: &Group, phase: Phase, dry_run: bool)-> Result<(), AlpsError>{
+    let report = group.sync(&SyncOptions::only(phase).dry_run(dry_run))?;
+    print_report(group, &report, &[phase], dry_run)
+}
 
-    let mut num_packages = 0;
-
-    let packages = util::get_entries(&util::read_label("[PACKAGES]", group, home_dir))
-        .filter_map(|package|{
-            num_packages += 1;
-
-            let handle = Command::new("pacman")
-                .args(["-Sg", package])
-                .stdout(Stdio::piped())
-                .output()
-                .unwrap();
-
-            if Command::new("pacman")
-                .args(["-Q", package])
-                .output()
-                .unwrap()
-                .status
-                .success()
-            {
-                eprintln!(
-                    "{} Package ({}) already installed to system!",
-                    "[!]".yellow(),
-                    package.yellow()
-                );
-                None
-            }
-            else if handle.status.success(){
-                let missing = String::from_utf8(handle.stdout)
-                    .unwrap()
-                    .split_whitespace()
-                    .filter_map(|entry|{
-                        if entry != package{
-                            Some(entry.to_owned())
-                        }
-                        else{
-                            None
-                        }
-                    })
-                    .any(|entry|{
-                        !Command::new("pacman")
-                            .args(["-Q", &entry])
-                            .output()
-                            .unwrap()
-                            .status
-                            .success()
-                });
-
-                if !missing{
-                    eprintln!(
-                        "{} Package group ({}) already installed to system!",
-                        "[!]".yellow(),
-                        package.yellow()
-                    );
-                    None
-                }
-                else{
-                    println!(
-                        "{} Installing package group ({}) to system...",
-                        "[+]".purple(),
-                        package.purple()
-                    );
-                    
-                    Some(package.to_owned())                   
-                }
-            }
-            else{
-                let handle_package = Command::new("pacman")
-                    .args(["-Ss", &("^".to_owned() + package + "$")])
-                    .output()
-                    .unwrap();
-                
-                if handle_package.status.success(){
-                    println!(
-                        "{} Installing package ({}) to system...",
-                        "[+]".purple(),
-                        package.purple()
-                    );
-                    Some(package.to_owned())
-                }
-                else{
-                    eprintln!(
-                        "{} Package ({}) does not exist in repository!",
-                        "[!]".yellow(),
-                        package.yellow()
-                    );
-                    util::config_write(group, "[PACKAGES]", package, home_dir, false);
+// Prints a report phase by phase, the hooks run around a phase under their own heading.
+fn print_report(group: &Group, report: &SyncReport, phases: &[Phase], dry_run: bool)-> Result<(), AlpsError>{
+    for phase in phases{
+        if let Some(hook) = phase.before(){
+            print_hook(group, report, hook, dry_run);
+        }
 
-                    None
-                }
-            }
-        }).collect::<Vec<String>>();
-    
-    if !packages.is_empty(){
-        match Command::new("sudo")
-            .args(["pacman", "-S"])
-            .args(packages.as_slice())
-            .status()
-        {
-            Ok(_) =>{
-                println!(
-                    "{} Synced ({}/{num_packages}) packages...",
-                    "[~]".purple(),
-                    packages.len()
-                );
-            }
-            Err(_) =>{
-                eprintln!(
-                    "{} Failed to sync packages!",
-                    "[!!!]".red()
-                );
+        print_heading(group, phase.name(), dry_run);
+        let entries = report.phase(*phase).collect::<Vec<&SyncEntry>>();
 
-                std::process::exit(1);
-            }
+        if entries.is_empty(){
+            eprintln!(
+                "{} No {} to sync in group ({})!",
+                "[!]".yellow(),
+                phase.name(),
+                group.name().yellow()
+            );
+        }
+        else{
+            print_entries(group, &entries, phase.name(), dry_run);
+        }
+
+        if let Some(hook) = phase.after(){
+            print_hook(group, report, hook, dry_run);
         }
     }
-    else{
-        eprintln!(
-            "{} No packages to sync in group ({})!",
-            "[!]".yellow(),
-            group.yellow()
-        );
+
+    if report.failed() > 0{
+        return Err(AlpsError::Failed(report.failed()));
     }
+    Ok(())
 }
 
-pub fn sync_config(home_dir: &str, group: &str){
+// Hooks without scripts are left out.
+fn print_hook(group: &Group, report: &SyncReport, hook: Hook, dry_run: bool){
+    let entries = report.hook(hook).collect::<Vec<&SyncEntry>>();
+
+    if !entries.is_empty(){
+        let name = hook.name().to_owned() + " hooks";
+
+        print_heading(group, &name, dry_run);
+        print_entries(group, &entries, &name, dry_run);
+    }
+}
+
+fn print_heading(group: &Group, name: &str, dry_run: bool){
     println!(
-        "{} Syncing configs of group ({}) {}",
+        "{} {} {} of group ({}) {}",
         "=====".purple(),
-        group.purple(),
+        if dry_run{ "Dry run of" } else{ "Syncing" },
+        name,
+        group.name().purple(),
         "=====".purple()
     );
+}
 
-    let mut num_configs = 0;
-
-    let configs = util::get_entries(&util::read_label("[CONFIGS]", group, home_dir))
-        .map(|config| config.to_owned())
-        .collect::<Vec<String>>();
-
-    if !configs.is_empty(){
-        for config in &configs
-        {
-            let mut path_dst = config.to_owned();
-            if let Some(name) = config.split('/').last().unwrap()
-                .rsplit_once('_')
-            {
-                if name.1.parse::<usize>().is_ok(){
-                    path_dst = config.rsplit_once('_').unwrap().0.to_owned();
-                }
-            }
-            util::to_userdir(&mut path_dst);
-
-            let config_name = config.split('/').last().unwrap();
-            let path_src = home_dir.to_owned() + group + "/configs/" + config_name;
+fn print_entries(group: &Group, entries: &[&SyncEntry], name: &str, dry_run: bool){
+    for entry in entries{
+        // Hooks are scripts.
+        let noun = match entry.phase{
+            Phase::Packages => "Package",
+            Phase::Flatpaks => "Flatpak",
+            Phase::Tools => "Tool",
+            Phase::Configs => "Config",
+            Phase::Scripts => "Script",
+        };
+
+        // Entries of included groups are shown as <group>/<entry>.
+        let entry_name = if entry.group == group.name(){
+            entry.name.to_owned()
+        }
+        else{
+            entry.group.to_owned() + "/" + &entry.name
+        };
 
-            if Path::new(&path_src).exists(){
-                num_configs += 1;
-                util::copy_dir(path_src, path_dst);
-    
+        match &entry.outcome{
+            Outcome::Synced =>{
                 println!(
-                    "{} Synced config ({})!",
+                    "{} Synced {} ({})!",
                     "[~]".purple(),
-                    config_name.purple(),
+                    noun.to_lowercase(),
+                    entry_name.purple()
                 );
             }
-            else{
+            Outcome::Present =>{
                 eprintln!(
-                    "{} Contents of config ({}) do not exist!",
+                    "{} {} ({}) already installed to system!",
                     "[!]".yellow(),
-                    config_name.yellow()
+                    noun,
+                    entry_name.yellow()
+                );
+            }
+            Outcome::Skipped(reason) =>{
+                eprintln!(
+                    "{} {} ({}) skipped, {}...",
+                    "[!]".yellow(),
+                    noun,
+                    entry_name.yellow(),
+                    reason
+                );
+            }
+            Outcome::Failed(reason) =>{
+                eprintln!(
+                    "{} {} ({}) failed: {}!",
+                    "[!]".yellow(),
+                    noun,
+                    entry_name.yellow(),
+                    reason
+                );
+            }
+            Outcome::Planned(action) =>{
+                println!(
+                    "{} {} ({}) would {}",
+                    "[~]".purple(),
+                    noun,
+                    entry_name.purple(),
+                    action
                 );
-
-                util::config_write(group, "[CONFIGS]", &config, home_dir, false);
             }
         }
+    }
 
+    if dry_run{
         println!(
-            "{} Synced ({num_configs}/{}) configs...",
+            "{} Would sync ({}/{}) {}...",
             "[~]".purple(),
-            configs.len()
+            entries.iter().filter(|entry| matches!(entry.outcome, Outcome::Planned(_))).count(),
+            entries.len(),
+            name
         );
     }
     else{
-        eprintln!(
-            "{} No configs to sync in group ({})",
-            "[!]".yellow(),
-            group.yellow()
+        println!(
+            "{} Synced ({}/{}) {}...",
+            "[~]".purple(),
+            entries.iter().filter(|entry| entry.outcome == Outcome::Synced).count(),
+            entries.len(),
+            name
         );
     }
 }
 
-pub fn sync_script(home_dir: &str, group: &str){
+pub fn query_help(){
+    println!("{} {{-Q}} [flag]", "usage:".white());
+    println!("{}", "flags:".white());
+    println!("   {{-g --group}} [?group(s)] : query installed group(s)");
+    println!("   {{-c --config}} [group] [?config(s)]: query installed configs of a group");
+    println!("   {{-p --package}} [group] [?packages(s)] : query installed packages of a group");
+    println!("   {{-s --script}} [group] [?scripts(s)] : query installed scripts of a group");
+    println!("   {{-f --flatpak}} [group] [?flatpak(s)] : query installed flatpaks of a group");
+    println!("   {{-t --tool}} [group] [?tool(s)] : query installed tools of a group");
+    println!("   {{-b --backend}} [group] : query package manager used by a group");
+    println!("   {{-i --include}} [group] [?group(s)] : query groups included by a group");
+    println!("   {{--status}} [group] : report how the system has drifted from a group");
+    println!("{} ? = optional", "hint:".white());
+}
+
+pub fn query_status(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    let entries = group.status()?;
+
     println!(
-        "{} Syncing scripts of group ({}) {}",
+        "{} Status of group ({}) {}",
         "=====".purple(),
-        group.purple(),
+        group.name().purple(),
         "=====".purple()
     );
 
-    let mut num_scripts = 0;
-
-    let scripts = util::get_entries(&util::read_label("[SCRIPTS]", group, home_dir))
-        .map(|script| script.to_owned() )
-        .collect::<Vec<String>>();
-
-    if !scripts.is_empty(){
-        for script in &scripts{
-            let script_path = home_dir.to_owned() + group + "/scripts/" + script;
-            let mut handle = Command::new("/".to_owned() + &script_path);
+    for entry in &entries{
+        let noun = match entry.phase{
+            Phase::Packages => "Package",
+            Phase::Flatpaks => "Flatpak",
+            Phase::Tools => "Tool",
+            Phase::Configs => "Config",
+            Phase::Scripts => "Script",
+        };
+
+        let status = match &entry.status{
+            Status::Installed => String::from("installed"),
+            Status::Outdated => String::from("outdated"),
+            Status::Missing => String::from("missing"),
+            Status::Identical => String::from("identical"),
+            Status::Modified => String::from("modified"),
+            Status::NeverRun => String::from("never run"),
+            Status::Succeeded => String::from("succeeded"),
+            Status::Failed => String::from("failed"),
+            Status::Skipped(reason) => "skipped, ".to_owned() + reason,
+            Status::Unknown(reason) => "unknown, ".to_owned() + reason,
+        };
+
+        // Entries of included groups are shown as <group>/<entry>.
+        let entry_name = if entry.group == group.name(){
+            entry.name.to_owned()
+        }
+        else{
+            entry.group.to_owned() + "/" + &entry.name
+        };
 
+        if entry.status.in_sync(){
             println!(
-                "{} Running script ({})...",
-                "[~]".purple(),
-                script.purple()
+                "{} {} ({}) {}",
+                "[?]".blue(),
+                noun,
+                entry_name.blue(),
+                status
+            );
+        }
+        else{
+            eprintln!(
+                "{} {} ({}) {}",
+                "[!]".yellow(),
+                noun,
+                entry_name.yellow(),
+                status
             );
+        }
+    }
 
-            match handle.status(){
-                Ok(_) => {
-                    num_scripts += 1;
+    let drifted = entries.iter()
+        .filter(|entry| !entry.status.in_sync())
+        .count();
 
-                    println!(
-                        "{} Successfully ran script ({})...",
-                        "[~]".purple(),
-                        script.purple()
-                    );
-                }
-                Err(error) => {
-                    if error.kind() == ErrorKind::NotFound{
-                        eprintln!(
-                            "{} Contents of script ({}) do not exist!",
-                            "[!]".yellow(),
-                            script.yellow()
-                        );
-                    }
-                    else{
-                        eprintln!(
-                            "{} Script ({}) failed to run!",
-                            "[!]".yellow(),
-                            script.yellow()
-                        );
-                    }
-
-                    if Path::new(&script_path).is_dir(){
-                        fs::remove_dir_all(script_path);
-                    }
-                    else if Path::new(&script_path).is_file(){
-                        fs::remove_file(script_path);
-                    }
-                    util::config_write(group, "[SCRIPTS]", script, home_dir, false);
-                }
-            }
-        }
+    println!(
+        "({}/{}) entries match the system...",
+        entries.len() - drifted,
+        entries.len()
+    );
 
-        println!(
-            "{} Synced ({num_scripts}/{}) scripts...",
-            "[~]".purple(),
-            scripts.len()
-        );
+    if drifted > 0{
+        return Err(AlpsError::Failed(drifted));
     }
-    else{
-        eprintln!(
-            "{} No scripts to sync in group ({})",
-            "[!]".yellow(),
-            group.yellow()
-        );
-    }
-}
-
-pub fn query_help(){
-    println!("{} {{-Q}} [flag]", "usage:".white());
-    println!("{}", "flags:".white());
-    println!("   {{-g --group}} [?group(s)] : query installed group(s)");
-    println!("   {{-c --config}} [group] [?config(s)]: query installed configs of a group");       
-    println!("   {{-p --package}} [group] [?packages(s)] : query installed packages of a group");
-    println!("   {{-s --script}} [group] [?scripts(s)] : query installed scripts of a group");      
-    println!("{} ? = optional", "hint:".white());
+    Ok(())
 }
 
-pub fn query_group(args: Vec<String>, home_dir: &str){
-    let excludes = vec![
-        String::from(".git"), 
-        String::from(".."), 
-        String::from(".")
-    ];
-
+pub fn query_group(args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
     //Credit Raforawesome (programming God)
-    let groups: Vec<fs::DirEntry> = fs::read_dir(home_dir).unwrap()
-        .filter(|entry|{
-            let name = entry.as_ref()
-                .unwrap().file_name()
-                .into_string().unwrap();
-            let entry = entry.as_ref().unwrap()
-                .file_type().unwrap();
-
-            entry.is_dir() && !excludes.contains(&name)
-        }).map(|x| x.unwrap()).collect();
+    let groups = store.groups()?;
 
     if !groups.is_empty(){
         if !args.is_empty(){
             let mut status = 0;
 
             for arg in &args{
-                let contains = groups.iter()
-                    .any(|group|{
-                        let group = &group
-                            .file_name()
-                            .into_string()
-                            .unwrap();
-    
-                        arg == group
-                });
-                
-                if contains{
+                if groups.contains(arg){
                     println!(
                         "{} Group ({}) found...",
                         "[?]".blue(),
@@ -566,22 +717,26 @@ pub fn query_group(args: Vec<String>, home_dir: &str){
                         arg.yellow()
                     );
                     status += 1;
-                }               
+                }
             }
 
-            std::process::exit(status);
+            if status > 0{
+                return Err(AlpsError::Failed(status));
+            }
         }
         else{
-            for group in &groups{
-                let group = group.file_name().into_string().unwrap();
+            for name in &groups{
+                let group = store.group(name)?;
 
                 println!(
-                    "{} {} :: ({}) packages :: ({}) configs :: ({}) scripts", 
+                    "{} {} :: ({}) packages :: ({}) flatpaks :: ({}) tools :: ({}) configs :: ({}) scripts",
                     "[?]".blue(),
-                    group.blue(),
-                    util::get_entries(&util::read_label("[PACKAGES]", &group, home_dir)).count(),
-                    util::get_entries(&util::read_label("[CONFIGS]", &group, home_dir)).count(),
-                    util::get_entries(&util::read_label("[SCRIPTS]", &group, home_dir)).count()
+                    name.blue(),
+                    group.packages()?.len(),
+                    group.flatpaks()?.len(),
+                    group.tools()?.len(),
+                    group.configs()?.len(),
+                    group.scripts()?.len()
                 );
             }
             println!("({}) groups found...", groups.len());
@@ -593,18 +748,28 @@ pub fn query_group(args: Vec<String>, home_dir: &str){
             "[!]".yellow()
         );
     }
+    Ok(())
 }
 
-pub fn query_package(mut args: Vec<String>, home_dir: &str){
-    let mut group = String::new();
-    sift::missing_group(home_dir, &mut args, &mut group);
-    util::find(args, "[PACKAGES]", home_dir, &group, |package| package);
+pub fn query_package(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    // Listed in full, so entries of one package for several package managers tell apart.
+    let listing = args.is_empty();
+    find(args, "packages", &group.packages()?, group.name(), |package|{
+        if listing{
+            return package;
+        }
+        let spec = match package.split_once(':'){
+            Some((prefix, spec)) if prefix == "aur" || backend::BACKENDS.contains(&prefix) => spec,
+            _ => package,
+        };
+        backend::split_version(spec).0
+    })
 }
 
-pub fn query_config(mut args: Vec<String>, home_dir: &str){
-    let mut group = String::new();
-    sift::missing_group(home_dir, &mut args, &mut group);
-    util::find(args, "[CONFIGS]", home_dir, &group, |config|{
+pub fn query_config(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    find(args, "configs", &group.configs()?, group.name(), |config|{
         let x = config.rsplit_once('/').unwrap_or((config, config)).1;
 
         if let Some(name) = x.rsplit_once('_'){
@@ -618,63 +783,472 @@ pub fn query_config(mut args: Vec<String>, home_dir: &str){
         else{
             x
         }
-    });
+    })
 }
 
-pub fn query_script(mut args: Vec<String>, home_dir: &str){
-    let mut group = String::new();
-    sift::missing_group(home_dir, &mut args, &mut group);
-    util::find(args, "[SCRIPTS]", home_dir, &group, |script| script);
+pub fn query_script(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    find(args, "scripts", &group.scripts()?, group.name(), |script| script)
 }
 
-pub fn edit_help(){
-    println!("{} {{-E}} [flag]", "usage".white());
+pub fn query_flatpak(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    find(args, "flatpaks", &group.flatpaks()?, group.name(), |flatpak|{
+        flatpak.rsplit(':').next().unwrap_or(flatpak)
+    })
+}
+
+pub fn query_tool(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+
+    let tools = group.tools()?
+        .into_iter()
+        .map(|(tool, spec)| tool + ":" + &spec)
+        .collect::<Vec<String>>();
+
+    find(args, "tools", &tools, group.name(), |tool|{
+        let spec = tool.split_once(':').map(|(_, spec)| spec).unwrap_or(tool);
+        backend::split_version(spec).0
+    })
+}
+
+pub fn query_backend(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+
+    let set = group.backend_name()?.is_some();
+    let backend = group.backend()?;
+
+    println!(
+        "{} {}/{}/{} ({})",
+        "[?]".blue(),
+        group.name().blue(),
+        "backend".blue(),
+        backend.name().blue(),
+        if set{ "set" } else{ "detected" }
+    );
+    Ok(())
+}
+
+pub fn query_include(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    find(args, "includes", &group.includes()?, group.name(), |include| include)
+}
+
+fn find(args: Vec<String>, label: &str, entries: &[String], group: &str, mutate: impl Fn(&str)-> &str)-> Result<(), AlpsError>{
+    if !args.is_empty(){
+        let mut status = 0;
+
+        for arg in &args{
+            let mut found = vec![];
+
+            for entry in entries{
+                if arg == entry || arg == mutate(entry){
+                    found.push(entry);
+                }
+            }
+
+            if !found.is_empty(){
+                for found in found{
+                    println!(
+                        "{} Found {}/{}/{} ",
+                        "[?]".blue(),
+                        group.blue(),
+                        label.blue(),
+                        found.blue()
+                    );
+                }
+            }
+            else{
+                eprintln!(
+                    "{} {}/{}/{} not found!",
+                    "[!]".yellow(),
+                    group.yellow(),
+                    label.yellow(),
+                    arg.yellow()
+                );
+                status += 1;
+            }
+        }
+
+        if status > 0{
+            return Err(AlpsError::Failed(status));
+        }
+    }
+    else{
+        for entry in entries{
+            print!("{}, ", mutate(entry).blue());
+        }
+        println!("({}) entries found...", entries.len());
+    }
+    Ok(())
+}
+
+pub fn diff_help(){
+    println!("{} {{-D}} [flag]", "usage:".white());
     println!("{}", "flags:".white());
-    println!("   {{-g --group}} [group(s)] : edit installed group(s)");
-    println!("   {{-c --config}} [group] [config(s)]: edit installed configs of a group");       
-    println!("   {{-s --script}} [group] [scripts(s)] : edit installed scripts of a group");      
+    println!("   {{-c --config}} [group] [?config(s)] : diff configs of a group against the system");
 }
 
-pub fn edit_group(mut args: Vec<String>, home_dir: &str, editor: String){
-    sift::missing_args(&mut args, 1);
+pub fn diff_config(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    let mut diffs = group.diff_configs()?;
+    let mut status = 0;
 
-    for arg in &args{
-        let config_path = home_dir.to_owned() 
-            + arg 
-            + "/" 
-            + arg 
-            + ".conf";
+    if !args.is_empty(){
+        for arg in &args{
+            if !diffs.iter().any(|diff| diff_matches(diff, arg)){
+                eprintln!(
+                    "{} {}/{}/{} not found!",
+                    "[!]".yellow(),
+                    group.name().yellow(),
+                    "configs".yellow(),
+                    arg.yellow()
+                );
+                status += 1;
+            }
+        }
+        diffs.retain(|diff| args.iter().any(|arg| diff_matches(diff, arg)));
+    }
 
-        if Path::new(&config_path).is_file(){
-            util::edit_file(&config_path, &editor);
+    println!(
+        "{} Diff of configs of group ({}) {}",
+        "=====".purple(),
+        group.name().purple(),
+        "=====".purple()
+    );
+
+    for diff in &diffs{
+        if diff.locked{
+            println!(
+                "{} Config ({}) is secret, not compared (set ${} to compare it)",
+                "[~]".purple(),
+                diff.entry.purple(),
+                secret::VAR
+            );
         }
-        else{
+        else if let Some(error) = &diff.error{
             eprintln!(
-                "{} Group ({}) config does not exist!",
+                "{} Config ({}) could not be diffed: {}",
                 "[!]".yellow(),
-                arg.yellow()
+                diff.entry.yellow(),
+                error
             );
+            status += 1;
+        }
+        else if diff.files.is_empty(){
+            eprintln!(
+                "{} Config ({}) contents do not exist!",
+                "[!]".yellow(),
+                diff.entry.yellow()
+            );
+            status += 1;
+        }
+        else if !diff.changed(){
+            println!(
+                "{} Config ({}) matches {}",
+                "[~]".purple(),
+                diff.entry.purple(),
+                diff.target.display()
+            );
+        }
+        else if diff.is_dir(){
+            println!(
+                "{} Config ({}) differs from {}: {} changed, {} new, {} unchanged",
+                "[~]".purple(),
+                diff.entry.purple(),
+                diff.target.display(),
+                diff.count(|change| matches!(change, Change::Text(_) | Change::Binary | Change::Conflict)),
+                diff.count(|change| *change == Change::Created),
+                diff.count(|change| *change == Change::Unchanged)
+            );
+
+            for file in &diff.files{
+                let change = match file.change{
+                    Change::Unchanged => continue,
+                    Change::Created => "new",
+                    Change::Text(_) => "changed",
+                    Change::Binary => "changed (binary)",
+                    Change::Conflict => "not a file",
+                };
+                println!("    {} {}", change, file.target.display());
+            }
+        }
+        else{
+            let file = &diff.files[0];
+
+            match &file.change{
+                Change::Text(text) =>{
+                    println!(
+                        "{} Config ({}) differs from {}:",
+                        "[~]".purple(),
+                        diff.entry.purple(),
+                        diff.target.display()
+                    );
+                    print_diff(text);
+                }
+                Change::Created =>{
+                    println!(
+                        "{} Config ({}) would create {}",
+                        "[~]".purple(),
+                        diff.entry.purple(),
+                        diff.target.display()
+                    );
+                }
+                Change::Binary =>{
+                    println!(
+                        "{} Config ({}) binary file differs from {}",
+                        "[~]".purple(),
+                        diff.entry.purple(),
+                        diff.target.display()
+                    );
+                }
+                _ =>{
+                    println!(
+                        "{} Config ({}) would replace {}, which is not a file",
+                        "[~]".purple(),
+                        diff.entry.purple(),
+                        diff.target.display()
+                    );
+                }
+            }
+        }
+    }
+
+    if diffs.is_empty() && args.is_empty(){
+        eprintln!(
+            "{} No configs to diff in group ({})!",
+            "[!]".yellow(),
+            group.name().yellow()
+        );
+    }
+
+    if status > 0{
+        return Err(AlpsError::Failed(status));
+    }
+    Ok(())
+}
+
+fn diff_matches(diff: &ConfigDiff, arg: &str)-> bool{
+    Config::parse(&diff.entry).matches(arg)
+}
+
+fn print_diff(text: &str){
+    for line in text.lines(){
+        if line.starts_with("+++") || line.starts_with("---"){
+            println!("{}", line.bold());
+        }
+        else if line.starts_with('+'){
+            println!("{}", line.green());
+        }
+        else if line.starts_with('-'){
+            println!("{}", line.red());
+        }
+        else if line.starts_with("@@"){
+            println!("{}", line.cyan());
+        }
+        else{
+            println!("{line}");
+        }
+    }
+}
+
+pub fn update_help(){
+    println!("{} {{-U}} [flag]", "usage:".white());
+    println!("{}", "flags:".white());
+    println!("   {{-c --config}} [group] [?config(s)] : copy the live contents of configs back into a group");
+}
+
+pub fn update_config(mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    let mut configs = group.manifest()?.configs;
+    let mut status = 0;
+
+    if !args.is_empty(){
+        for arg in &args{
+            if !configs.iter().any(|config| config.matches(arg)){
+                eprintln!(
+                    "{} {}/{}/{} not found!",
+                    "[!]".yellow(),
+                    group.name().yellow(),
+                    "configs".yellow(),
+                    arg.yellow()
+                );
+                status += 1;
+            }
+        }
+        configs.retain(|config| args.iter().any(|arg| config.matches(arg)));
+    }
+
+    for config in &configs{
+        match group.capture_config(config)?{
+            Outcome::Present =>{
+                eprintln!(
+                    "{} Config ({}) already up to date!",
+                    "[!]".yellow(),
+                    config.entry().yellow()
+                );
+            }
+            Outcome::Failed(reason) =>{
+                eprintln!(
+                    "{} Config ({}) failed: {}!",
+                    "[!]".yellow(),
+                    config.entry().yellow(),
+                    reason
+                );
+                status += 1;
+            }
+            _ =>{
+                println!(
+                    "{} Updated {}/{}/{}",
+                    "[+]".green(),
+                    group.name().green(),
+                    "configs".green(),
+                    config.source.green()
+                );
+            }
         }
     }
+
+    if configs.is_empty() && args.is_empty(){
+        eprintln!(
+            "{} No configs to update in group ({})!",
+            "[!]".yellow(),
+            group.name().yellow()
+        );
+    }
+
+    if status > 0{
+        return Err(AlpsError::Failed(status));
+    }
+    Ok(())
 }
 
-pub fn edit_config(mut args: Vec<String>, home_dir: &str, editor: String){
-    let mut group = String::new();
-    sift::missing_group(home_dir, &mut args, &mut group);
-    sift::missing_args(&mut args, 1);
+pub fn rollback_help(store: &Store)-> Result<(), AlpsError>{
+    println!("{} {{--rollback}} [?snapshot]", "usage:".white());
+    println!("   restores the files a config sync overwrote, from the latest snapshot unless one is given");
+
+    let snapshots = store.snapshots()?;
+    if !snapshots.is_empty(){
+        println!("{}", "snapshots:".white());
+        for snapshot in snapshots{
+            println!("   {snapshot}");
+        }
+    }
+    Ok(())
+}
+
+pub fn rollback(args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    let name = match args.as_slice(){
+        [] => match store.snapshots()?.pop(){
+            Some(name) => name,
+            None => return Err(AlpsError::Usage(String::from("No snapshots to roll back!"))),
+        },
+        [name] => name.to_owned(),
+        _ => return Err(AlpsError::Usage(String::from("Expected one snapshot!"))),
+    };
+
+    let snapshot = store.snapshot(&name)?;
+    println!(
+        "{} Rolling back snapshot ({}) of group ({}) {}",
+        "=====".purple(),
+        snapshot.name().purple(),
+        snapshot.group().purple(),
+        "=====".purple()
+    );
+
+    let mut status = 0;
+    for (path, restore) in snapshot.rollback(){
+        match restore{
+            Restore::Restored =>{
+                println!(
+                    "{} Restored ({})!",
+                    "[~]".purple(),
+                    path.purple()
+                );
+            }
+            Restore::Removed =>{
+                println!(
+                    "{} Removed ({}), it did not exist before the sync!",
+                    "[-]".green(),
+                    path.green()
+                );
+            }
+            Restore::Failed(reason) =>{
+                eprintln!(
+                    "{} File ({}) failed to restore: {}!",
+                    "[!]".yellow(),
+                    path.yellow(),
+                    reason
+                );
+                status += 1;
+            }
+        }
+    }
+
+    if status > 0{
+        return Err(AlpsError::Failed(status));
+    }
+    Ok(())
+}
+
+pub fn edit_help(){
+    println!("{} {{-E}} [flag]", "usage".white());
+    println!("{}", "flags:".white());
+    println!("   {{-g --group}} [group(s)] : edit installed group(s)");
+    println!("   {{-c --config}} [group] [config(s)]: edit installed configs of a group");
+    println!("   {{-s --script}} [group] [scripts(s)] : edit installed scripts of a group");
+}
+
+pub fn edit_group(args: Vec<String>, store: &Store, editor: String)-> Result<(), AlpsError>{
+    sift::missing_args(&args, 1)?;
 
     for arg in &args{
-        let config_path = home_dir.to_owned()
-            + &group
+        match store.group(arg){
+            Ok(group) =>{
+                let config_path = Manifest::path(group.name(), group.home_dir());
+
+                // Loading first migrates an old .conf, or writes a blank manifest for an empty group.
+                if !Path::new(&config_path).is_file(){
+                    group.save(&group.manifest()?)?;
+                }
+                edit_file(&config_path, &editor)?;
+            }
+            Err(_) =>{
+                eprintln!(
+                    "{} Group ({}) config does not exist!",
+                    "[!]".yellow(),
+                    arg.yellow()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn edit_config(mut args: Vec<String>, store: &Store, editor: String)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
+
+    let configs = group.manifest()?.configs;
+
+    for arg in &args{
+        let config_path = group.path()
             + "/configs/"
             + arg;
         let config_name = config_path.split("/configs/")
             .last()
             .unwrap();
 
+        // The arg may point inside a stored directory, e.g. nvim_1/init.lua.
+        let secret = configs.iter()
+            .any(|config| config.secret && arg.split('/').next() == Some(config.source.as_str()));
+
         let path = Path::new(&config_path);
-        if path.is_file(){
-            util::edit_file(&config_path, &editor);
+        if path.is_file() && secret{
+            edit_secret(path, config_name, &editor)?;
+        }
+        else if path.is_file(){
+            edit_file(&config_path, &editor)?;
         }
         else if path.is_dir(){
             eprintln!(
@@ -687,36 +1261,89 @@ pub fn edit_config(mut args: Vec<String>, home_dir: &str, editor: String){
             eprintln!(
                 "{} {}/{}/{} does not exist!",
                 "[!]".yellow(),
-                group.yellow(),
+                group.name().yellow(),
                 "configs".yellow(),
                 arg.yellow()
             );
         }
     }
+    Ok(())
 }
 
-pub fn edit_script(mut args: Vec<String>, home_dir: &str, editor: String){
-    let mut group = String::new();
-    sift::missing_group(home_dir, &mut args, &mut group);
-    sift::missing_args(&mut args, 1);
+pub fn edit_script(mut args: Vec<String>, store: &Store, editor: String)-> Result<(), AlpsError>{
+    let group = sift::missing_group(store, &mut args)?;
+    sift::missing_args(&args, 1)?;
 
     for arg in &args{
-        let config_path = home_dir.to_owned()
-            + &group
+        let config_path = group.path()
             + "/scripts/"
             + arg;
 
         if Path::new(&config_path).is_file(){
-            util::edit_file(&config_path, &editor);
+            edit_file(&config_path, &editor)?;
         }
         else{
             eprintln!(
                 "{} {}/{}/{} does not exist!",
                 "[!]".yellow(),
-                group.yellow(),
+                group.name().yellow(),
                 "scripts".yellow(),
                 arg.yellow()
             );
         }
     }
+    Ok(())
+}
+
+// Decrypts to a file only the user can read, encrypting it back into the group once the
+// editor exits. The plain copy is removed either way.
+fn edit_secret(path: &Path, config_name: &str, editor: &str)-> Result<(), AlpsError>{
+    let contents = secret::read(path)?;
+    let temp = env::temp_dir().join("alps-".to_owned() + &process::id().to_string() + "-" + &config_name.replace('/', "_"));
+
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&temp)?
+        .write_all(&contents)?;
+
+    let result = match Command::new(editor).arg(&temp).status(){
+        Ok(_) => fs::read(&temp).map_err(AlpsError::from),
+        Err(_) => Err(AlpsError::invalid("editor", editor)),
+    };
+    let _ = fs::remove_file(&temp);
+
+    let edited = result?;
+    if edited != contents{
+        secret::write(path, &edited)?;
+    }
+
+    println!(
+        "{} Editing file ({})...",
+        "[%]".cyan(),
+        config_name.cyan()
+    );
+    Ok(())
+}
+
+fn edit_file(file_path: &str, editor: &str)-> Result<(), AlpsError>{
+    match Command::new(editor)
+        .arg(file_path)
+        .status()
+    {
+            Ok(_) =>{
+                let file_name = file_path.split("/configs/")
+                    .last()
+                    .unwrap_or(file_path);
+
+                println!(
+                    "{} Editing file ({})...",
+                    "[%]".cyan(),
+                    file_name.cyan()
+                );
+                Ok(())
+            }
+            Err(_) => Err(AlpsError::invalid("editor", editor)),
+    }
 }
@@ -0,0 +1,117 @@
+use std::process::Command;
+use crate::util;
+use crate::error::AlpsError;
+use crate::manifest::Remote;
+
+pub const FLATHUB: (&str, &str) = ("flathub", "https://dl.flathub.org/repo/flathub.flatpakrepo");
+
+// Entries are written as [user:|system:]<remote>:<app-id>, installing system-wide unless
+// prefixed with user:.
+pub struct Flatpak<'a>{
+    pub user: bool,
+    pub remote: &'a str,
+    pub app: &'a str,
+}
+
+impl<'a> Flatpak<'a>{
+    pub fn parse(entry: &'a str)-> Option<Flatpak<'a>>{
+        let segments = entry.split(':').collect::<Vec<&str>>();
+
+        let (user, remote, app) = match segments.as_slice(){
+            [remote, app] => (false, *remote, *app),
+            ["user", remote, app] => (true, *remote, *app),
+            ["system", remote, app] => (false, *remote, *app),
+            _ => return None,
+        };
+
+        if remote.is_empty() || app.is_empty(){
+            None
+        }
+        else{
+            Some(Flatpak{ user, remote, app })
+        }
+    }
+
+    pub fn installed(&self)-> bool{
+        Command::new("flatpak")
+            .args(["info", scope(self.user), self.app])
+            .output()
+            .map(|handle| handle.status.success())
+            .unwrap_or(false)
+    }
+
+    // Only checkable when the remote is already configured on this host, so anything else
+    // is taken on trust and verified by flatpak at sync time.
+    pub fn exists(&self)-> bool{
+        if !has_remote(self.user, self.remote){
+            return true;
+        }
+
+        Command::new("flatpak")
+            .args(["remote-info", scope(self.user), self.remote, self.app])
+            .output()
+            .map(|handle| handle.status.success())
+            .unwrap_or(false)
+    }
+}
+
+pub fn scope(user: bool)-> &'static str{
+    if user{ "--user" } else{ "--system" }
+}
+
+pub fn available()-> bool{
+    util::in_path("flatpak")
+}
+
+// The url of a remote declared by the group, flathub being known without one.
+pub fn find_remote(remote: &str, remotes: &[Remote])-> Option<String>{
+    remotes.iter()
+        .find(|entry| entry.name == remote)
+        .map(|entry| entry.url.to_owned())
+        .or_else(||{
+            if remote == FLATHUB.0{
+                Some(FLATHUB.1.to_owned())
+            }
+            else{
+                None
+            }
+        })
+}
+
+pub fn has_remote(user: bool, remote: &str)-> bool{
+    Command::new("flatpak")
+        .args(["remotes", scope(user), "--columns=name"])
+        .output()
+        .map(|handle|{
+            String::from_utf8_lossy(&handle.stdout)
+                .lines()
+                .any(|line| line.trim() == remote)
+        })
+        .unwrap_or(false)
+}
+
+pub fn add_remote(user: bool, remote: &str, url: &str)-> Result<(), AlpsError>{
+    util::run(
+        Command::new("flatpak")
+            .args(["remote-add", "--if-not-exists", scope(user), remote, url]),
+        "flatpak"
+    )
+}
+
+pub fn install(user: bool, remote: &str, apps: &[String])-> Result<(), AlpsError>{
+    util::run(
+        Command::new("flatpak")
+            .args(["install", scope(user), remote])
+            .args(apps),
+        "flatpak"
+    )
+}
+
+pub fn uninstall(user: bool, apps: &[String])-> Result<(), AlpsError>{
+    util::run(
+        Command::new("flatpak")
+            .args(["uninstall", scope(user)])
+            .args(apps),
+        "flatpak"
+    )
+}
@@ -0,0 +1,472 @@
+use std::{
+    fs,
+    path::Path,
+};
+use crate::util;
+use crate::backend::{self, PackageBackend};
+use crate::error::AlpsError;
+use crate::manifest::{Manifest, Config, Mode, Remote};
+use crate::sync::{self, SyncOptions, SyncReport, Outcome};
+use crate::diff::{self, ConfigDiff};
+use crate::backup::{self, Snapshot};
+use crate::state::{self, State};
+use crate::status::{self, StatusEntry};
+use crate::ignore::Ignore;
+use crate::template;
+use crate::attributes;
+
+// Names in the store that are never groups.
+pub const EXCLUDES: [&str; 5] = [".git", "..", ".", backup::LEGACY_DIR, state::LEGACY_DIR];
+// Machine-local files older versions kept in the store, as (old, new) names.
+const MOVED: [(&str, &str); 3] = [
+    (backup::LEGACY_DIR, backup::DIR),
+    (state::LEGACY_DIR, state::DIR),
+    (template::LEGACY_FILE, template::FILE),
+];
+
+// The directory holding every group, normally ~/.config/alps/, along with the directory
+// of what only concerns this machine (snapshots, script runs and template variables),
+// normally $XDG_STATE_HOME/alps/, so sharing the store never shares those.
+pub struct Store{
+    home_dir: String,
+    state_dir: String,
+}
+
+impl Store{
+    pub fn open()-> Result<Store, AlpsError>{
+        Store::at(util::user_home()? + "/.config/alps/", util::state_home()? + "/alps/")
+    }
+
+    pub fn at(home_dir: impl Into<String>, state_dir: impl Into<String>)-> Result<Store, AlpsError>{
+        let mut home_dir = home_dir.into();
+        if !home_dir.ends_with('/'){
+            home_dir.push('/');
+        }
+        let mut state_dir = state_dir.into();
+        if !state_dir.ends_with('/'){
+            state_dir.push('/');
+        }
+
+        fs::create_dir_all(&home_dir)?;
+        fs::create_dir_all(&state_dir)?;
+
+        let store = Store{ home_dir, state_dir };
+        store.migrate()?;
+        Ok(store)
+    }
+
+    // Moves whatever older versions left in the store to the state dir, unless the state
+    // dir already has its own.
+    fn migrate(&self)-> Result<(), AlpsError>{
+        for (old, new) in MOVED{
+            let old = self.home_dir.to_owned() + old;
+            let new = self.state_dir.to_owned() + new;
+
+            if Path::new(&old).exists() && !Path::new(&new).exists() && fs::rename(&old, &new).is_err(){
+                // Across filesystems, e.g. a store on another mount.
+                if Path::new(&old).is_dir(){
+                    util::copy_dir(&old, &new)?;
+                    fs::remove_dir_all(&old)?;
+                }
+                else{
+                    fs::copy(&old, &new)?;
+                    fs::remove_file(&old)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn home_dir(&self)-> &str{
+        &self.home_dir
+    }
+
+    pub fn state_dir(&self)-> &str{
+        &self.state_dir
+    }
+
+    pub fn groups(&self)-> Result<Vec<String>, AlpsError>{
+        let mut groups = Vec::new();
+
+        for entry in fs::read_dir(&self.home_dir)?{
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+
+            if entry.file_type()?.is_dir() && !EXCLUDES.contains(&name.as_str()){
+                groups.push(name);
+            }
+        }
+        groups.sort();
+
+        Ok(groups)
+    }
+
+    pub fn contains(&self, name: &str)-> bool{
+        !EXCLUDES.contains(&name) && Path::new(&(self.home_dir.to_owned() + name)).is_dir()
+    }
+
+    pub fn group(&self, name: &str)-> Result<Group, AlpsError>{
+        if !self.contains(name){
+            return Err(AlpsError::MissingGroup(name.to_owned()));
+        }
+
+        Ok(Group{ name: name.to_owned(), home_dir: self.home_dir.to_owned(), state_dir: self.state_dir.to_owned() })
+    }
+
+    pub fn create_group(&self, name: &str)-> Result<Group, AlpsError>{
+        if name.is_empty() || EXCLUDES.contains(&name) || name.contains('/'){
+            return Err(AlpsError::invalid("group", name));
+        }
+
+        fs::create_dir_all(self.home_dir.to_owned() + name)?;
+        let group = self.group(name)?;
+
+        if !Path::new(&Manifest::path(name, &self.home_dir)).exists(){
+            group.manifest()?.save(name, &self.home_dir)?;
+        }
+        Ok(group)
+    }
+
+    pub fn remove_group(&self, name: &str)-> Result<(), AlpsError>{
+        let group = self.group(name)?;
+
+        fs::remove_dir_all(group.path())?;
+        let _ = fs::remove_file(State::path(name, &self.state_dir));
+
+        Ok(())
+    }
+
+    // Snapshots of files overwritten by config syncs, oldest first.
+    pub fn snapshots(&self)-> Result<Vec<String>, AlpsError>{
+        backup::list(&self.state_dir)
+    }
+
+    pub fn snapshot(&self, name: &str)-> Result<Snapshot, AlpsError>{
+        Snapshot::open(&self.state_dir, name)
+    }
+}
+
+#[derive(Clone)]
+pub struct Group{
+    name: String,
+    home_dir: String,
+    state_dir: String,
+}
+
+impl Group{
+    pub fn name(&self)-> &str{
+        &self.name
+    }
+
+    pub fn home_dir(&self)-> &str{
+        &self.home_dir
+    }
+
+    pub fn state_dir(&self)-> &str{
+        &self.state_dir
+    }
+
+    pub fn path(&self)-> String{
+        self.home_dir.to_owned() + &self.name
+    }
+
+    pub fn manifest(&self)-> Result<Manifest, AlpsError>{
+        Manifest::load(&self.name, &self.home_dir)
+    }
+
+    pub fn save(&self, manifest: &Manifest)-> Result<(), AlpsError>{
+        manifest.save(&self.name, &self.home_dir)
+    }
+
+    // What syncing the group did on this machine.
+    pub fn state(&self)-> Result<State, AlpsError>{
+        State::load(&self.name, &self.state_dir)
+    }
+
+    pub fn save_state(&self, state: &State)-> Result<(), AlpsError>{
+        state.save(&self.name, &self.state_dir)
+    }
+
+    fn entries(&self, label: &str)-> Result<Vec<String>, AlpsError>{
+        Ok(util::get_entries(&util::read_label(label, &self.name, &self.home_dir)?)
+            .map(|entry| entry.to_owned())
+            .collect())
+    }
+
+    fn write(&self, label: &str, entry: &str, mode: bool)-> Result<(), AlpsError>{
+        util::config_write(&self.name, label, entry, &self.home_dir, mode)
+    }
+
+    pub fn packages(&self)-> Result<Vec<String>, AlpsError>{
+        self.entries("[PACKAGES]")
+    }
+
+    pub fn flatpaks(&self)-> Result<Vec<String>, AlpsError>{
+        self.entries("[FLATPAKS]")
+    }
+
+    pub fn flatpak_remotes(&self)-> Result<Vec<Remote>, AlpsError>{
+        Ok(self.manifest()?.flatpak_remotes)
+    }
+
+    // Tools as (tool, name[@version]) pairs, e.g. ("cargo", "ripgrep").
+    pub fn tools(&self)-> Result<Vec<(String, String)>, AlpsError>{
+        let mut tools = Vec::new();
+
+        for (tool, label) in backend::TOOLS{
+            for spec in self.entries(label)?{
+                tools.push((tool.to_owned(), spec));
+            }
+        }
+        Ok(tools)
+    }
+
+    // Configs as stored in the group, e.g. home_dir/.config/alacritty_1.
+    pub fn configs(&self)-> Result<Vec<String>, AlpsError>{
+        self.entries("[CONFIGS]")
+    }
+
+    pub fn scripts(&self)-> Result<Vec<String>, AlpsError>{
+        self.entries("[SCRIPTS]")
+    }
+
+    // Groups included directly, as written in the manifest.
+    pub fn includes(&self)-> Result<Vec<String>, AlpsError>{
+        Ok(self.manifest()?.includes)
+    }
+
+    // The group and every group it includes, included groups first so that the including
+    // group syncs last and wins. Each group appears once, at its first position.
+    pub fn resolve(&self)-> Result<Vec<Group>, AlpsError>{
+        let mut groups = Vec::new();
+        self.resolve_into(&mut Vec::new(), &mut groups)?;
+
+        Ok(groups)
+    }
+
+    fn resolve_into(&self, stack: &mut Vec<String>, groups: &mut Vec<Group>)-> Result<(), AlpsError>{
+        if let Some(start) = stack.iter().position(|name| *name == self.name){
+            let cycle = stack[start..].join(" -> ") + " -> " + &self.name;
+            return Err(AlpsError::invalid("include cycle", &cycle));
+        }
+        else if groups.iter().any(|group| group.name == self.name){
+            return Ok(());
+        }
+
+        let store = Store{ home_dir: self.home_dir.to_owned(), state_dir: self.state_dir.to_owned() };
+        stack.push(self.name.to_owned());
+
+        for include in self.includes()?{
+            store.group(&include)?.resolve_into(stack, groups)?;
+        }
+
+        stack.pop();
+        groups.push(self.clone());
+
+        Ok(())
+    }
+
+    // Refuses includes that would form a cycle.
+    pub fn add_include(&self, name: &str)-> Result<(), AlpsError>{
+        let store = Store{ home_dir: self.home_dir.to_owned(), state_dir: self.state_dir.to_owned() };
+        store.group(name)?;
+
+        let manifest = self.manifest()?;
+        if manifest.includes.iter().any(|include| include == name){
+            return Ok(());
+        }
+
+        let mut included = manifest.clone();
+        included.includes.push(name.to_owned());
+        self.save(&included)?;
+
+        if let Err(error) = self.resolve(){
+            self.save(&manifest)?;
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    pub fn remove_include(&self, name: &str)-> Result<(), AlpsError>{
+        let mut manifest = self.manifest()?;
+        manifest.includes.retain(|include| include != name);
+
+        self.save(&manifest)
+    }
+
+    // The backend pinned with [BACKEND], if any.
+    pub fn backend_name(&self)-> Result<Option<String>, AlpsError>{
+        Ok(self.entries("[BACKEND]")?.into_iter().next())
+    }
+
+    pub fn backend(&self)-> Result<Box<dyn PackageBackend>, AlpsError>{
+        backend::select(&self.name, &self.home_dir)
+    }
+
+    pub fn add_package(&self, package: &str)-> Result<(), AlpsError>{
+        self.write("[PACKAGES]", package, true)
+    }
+
+    pub fn remove_package(&self, package: &str)-> Result<(), AlpsError>{
+        self.write("[PACKAGES]", package, false)
+    }
+
+    pub fn add_flatpak(&self, flatpak: &str)-> Result<(), AlpsError>{
+        self.write("[FLATPAKS]", flatpak, true)
+    }
+
+    pub fn remove_flatpak(&self, flatpak: &str)-> Result<(), AlpsError>{
+        self.write("[FLATPAKS]", flatpak, false)
+    }
+
+    // Replaces any existing url of the remote.
+    pub fn add_flatpak_remote(&self, remote: &str, url: &str)-> Result<(), AlpsError>{
+        self.remove_flatpak_remote(remote)?;
+        self.write("[FLATPAK_REMOTES]", &(remote.to_owned() + " " + url), true)
+    }
+
+    pub fn remove_flatpak_remote(&self, remote: &str)-> Result<(), AlpsError>{
+        for entry in self.entries("[FLATPAK_REMOTES]")?{
+            if entry.split(' ').next() == Some(remote){
+                self.write("[FLATPAK_REMOTES]", &entry, false)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn add_tool(&self, tool: &str, spec: &str)-> Result<(), AlpsError>{
+        self.write(tool_label(tool)?, spec, true)
+    }
+
+    pub fn remove_tool(&self, tool: &str, spec: &str)-> Result<(), AlpsError>{
+        self.write(tool_label(tool)?, spec, false)
+    }
+
+    // Copies the file or directory at path into the group and returns the name it was
+    // stored under. Copies of the same file name are told apart by their _N suffix.
+    // Without a mode the config is deployed the group's way. Modes, owners and symlinks
+    // are recorded in the manifest rather than copied.
+    pub fn add_config(&self, path: &str, mode: Option<Mode>, template: bool, secret: bool)-> Result<String, AlpsError>{
+        let mut config = path.to_owned();
+        util::to_template(&mut config);
+
+        let (_, config_name) = path.rsplit_once('/').unwrap_or(("", path));
+        let postfix = "_".to_owned()
+            + &(util::dup_count(config_name, &self.name, &self.home_dir)?+1).to_string();
+
+        let mut manifest = self.manifest()?;
+
+        // Templated and secret configs are written file by file, so they're copied even in
+        // groups linking their configs.
+        let mode = match mode{
+            Some(Mode::Symlink) if template || secret =>{
+                return Err(AlpsError::invalid("config mode", "templated and secret configs can't be symlinked"));
+            }
+            None if (template || secret) && manifest.config_mode == Some(Mode::Symlink) => Some(Mode::Copy),
+            mode => mode,
+        };
+
+        let ignore = Ignore::new(&manifest, None)?;
+        let attributes = attributes::record(Path::new(path), &ignore)?;
+
+        let stored = self.path() + "/configs/" + config_name + &postfix;
+        fs::create_dir_all(self.path() + "/configs")?;
+        if Path::new(path).is_dir(){
+            fs::create_dir_all(&stored)?;
+        }
+        sync::store_files(&ignore.targets(path, &stored)?, secret)?;
+
+        manifest.configs.push(Config{
+            mode,
+            template,
+            secret,
+            attributes,
+            ..Config::parse(&(config + &postfix))
+        });
+        self.save(&manifest)?;
+
+        Ok(config_name.to_owned() + &postfix)
+    }
+
+    // Takes the stored entry, e.g. home_dir/.config/alacritty_1.
+    pub fn remove_config(&self, config: &str)-> Result<(), AlpsError>{
+        self.write("[CONFIGS]", config, false)?;
+
+        let config_name = config.split('/').next_back().unwrap_or(config);
+        let config_path = self.path() + "/configs/" + config_name;
+
+        if Path::new(&config_path).is_dir(){
+            let _ = fs::remove_dir_all(config_path);
+        }
+        else{
+            let _ = fs::remove_file(config_path);
+        }
+        Ok(())
+    }
+
+    // Copies the script at path into the group and returns its name.
+    pub fn add_script(&self, path: &str)-> Result<String, AlpsError>{
+        let script_name = path.split('/').next_back().unwrap_or(path);
+
+        fs::create_dir_all(self.path() + "/scripts")?;
+        fs::copy(path, self.path() + "/scripts/" + script_name)?;
+        self.write("[SCRIPTS]", script_name, true)?;
+
+        Ok(script_name.to_owned())
+    }
+
+    pub fn remove_script(&self, script: &str)-> Result<(), AlpsError>{
+        self.write("[SCRIPTS]", script, false)?;
+        let _ = fs::remove_file(self.path() + "/scripts/" + script);
+
+        Ok(())
+    }
+
+    pub fn set_backend(&self, name: &str)-> Result<(), AlpsError>{
+        if backend::from_name(name).is_none(){
+            return Err(AlpsError::invalid("backend", name));
+        }
+
+        self.unset_backend()?;
+        self.write("[BACKEND]", name, true)
+    }
+
+    // Returns the backends that were set.
+    pub fn unset_backend(&self)-> Result<Vec<String>, AlpsError>{
+        let entries = self.entries("[BACKEND]")?;
+
+        for entry in &entries{
+            self.write("[BACKEND]", entry, false)?;
+        }
+        Ok(entries)
+    }
+
+    pub fn sync(&self, options: &SyncOptions)-> Result<SyncReport, AlpsError>{
+        sync::run(self, options)
+    }
+
+    // How far the system has drifted from every entry of the group.
+    pub fn status(&self)-> Result<Vec<StatusEntry>, AlpsError>{
+        status::run(self)
+    }
+
+    // Copies the live contents of a config back into the group, keeping its _N name.
+    pub fn capture_config(&self, config: &Config)-> Result<Outcome, AlpsError>{
+        sync::capture_config(self, config)
+    }
+
+    // Compares every config with the live system, in manifest order.
+    pub fn diff_configs(&self)-> Result<Vec<ConfigDiff>, AlpsError>{
+        self.manifest()?.configs
+            .iter()
+            .map(|config| diff::config(self, config))
+            .collect()
+    }
+}
+
+fn tool_label(tool: &str)-> Result<&'static str, AlpsError>{
+    backend::TOOLS.iter()
+        .find(|(kind, _)| *kind == tool)
+        .map(|(_, label)| *label)
+        .ok_or_else(|| AlpsError::invalid("tool", tool))
+}
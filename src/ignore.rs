@@ -0,0 +1,144 @@
+use std::path::{Path, PathBuf};
+use glob::{Pattern, MatchOptions};
+use crate::util;
+use crate::manifest::{Manifest, Config};
+use crate::error::AlpsError;
+
+const OPTIONS: MatchOptions = MatchOptions{
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+// Files left out of directory configs, both when syncing and capturing them. Patterns
+// come from the group's ignore list and the config's own, and work like .gitignore ones:
+// without a slash they match a name at any depth, e.g. *.log or Cache, otherwise a path
+// from the config's root, e.g. User/workspaceStorage. Ignoring a directory ignores all
+// that's in it.
+#[derive(Debug, Default)]
+pub(crate) struct Ignore{
+    names: Vec<Pattern>,
+    paths: Vec<Pattern>,
+}
+
+impl Ignore{
+    pub(crate) fn new(manifest: &Manifest, config: Option<&Config>)-> Result<Ignore, AlpsError>{
+        let mut ignore = Ignore::default();
+        let patterns = manifest.ignore.iter()
+            .chain(config.iter().flat_map(|config| &config.ignore));
+
+        for entry in patterns{
+            let trimmed = entry.trim_end_matches('/');
+            let pattern = Pattern::new(trimmed.trim_start_matches('/'))
+                .map_err(|error| AlpsError::invalid("ignore pattern", &(entry.to_owned() + ": " + error.msg)))?;
+
+            if trimmed.contains('/'){
+                ignore.paths.push(pattern);
+            }
+            else{
+                ignore.names.push(pattern);
+            }
+        }
+        Ok(ignore)
+    }
+
+    // Takes a path relative to the config's root.
+    pub(crate) fn matches(&self, relative: &Path)-> bool{
+        let names = relative.iter()
+            .filter_map(|name| name.to_str())
+            .any(|name| self.names.iter().any(|pattern| pattern.matches_with(name, OPTIONS)));
+
+        names || relative.ancestors()
+            .filter(|path| !path.as_os_str().is_empty())
+            .any(|path| self.paths.iter().any(|pattern| pattern.matches_path_with(path, OPTIONS)))
+    }
+
+    // util::copy_targets without the ignored files.
+    pub(crate) fn targets<S, D>(&self, src: S, dst: D)-> Result<Vec<(PathBuf, PathBuf)>, AlpsError>
+    where
+        S: AsRef<Path>,
+        D: AsRef<Path>,
+    {
+        let mut targets = util::copy_targets(&src, dst)?;
+        targets.retain(|(source, _)| !self.matches(source.strip_prefix(&src).unwrap_or(source)));
+
+        Ok(targets)
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn ignore(patterns: &[&str])-> Ignore{
+        let manifest = Manifest{
+            ignore: patterns.iter().map(|pattern| pattern.to_string()).collect(),
+            ..Manifest::default()
+        };
+
+        Ignore::new(&manifest, None).unwrap()
+    }
+
+    #[test]
+    fn names_match_at_any_depth(){
+        let ignore = ignore(&["*.log"]);
+
+        assert!(ignore.matches(Path::new("a.log")));
+        assert!(ignore.matches(Path::new("sub/dir/b.log")));
+        assert!(!ignore.matches(Path::new("log")));
+        assert!(!ignore.matches(Path::new("a.log.txt")));
+    }
+
+    #[test]
+    fn directories_match_their_contents(){
+        let ignore = ignore(&["Cache/"]);
+
+        assert!(ignore.matches(Path::new("Cache")));
+        assert!(ignore.matches(Path::new("Cache/data")));
+        assert!(ignore.matches(Path::new("profile/Cache/data")));
+        assert!(!ignore.matches(Path::new("CacheStorage")));
+    }
+
+    #[test]
+    fn paths_match_from_the_root(){
+        let ignore = ignore(&["User/workspaceStorage", "/top"]);
+
+        assert!(ignore.matches(Path::new("User/workspaceStorage")));
+        assert!(ignore.matches(Path::new("User/workspaceStorage/a/b")));
+        assert!(!ignore.matches(Path::new("Other/User/workspaceStorage")));
+        assert!(ignore.matches(Path::new("top")));
+        assert!(ignore.matches(Path::new("top/file")));
+        assert!(!ignore.matches(Path::new("sub/top")));
+    }
+
+    #[test]
+    fn path_globs_stay_within_a_directory(){
+        let ignore = ignore(&["logs/*.txt"]);
+
+        assert!(ignore.matches(Path::new("logs/a.txt")));
+        assert!(!ignore.matches(Path::new("logs/old/a.txt")));
+    }
+
+    #[test]
+    fn config_patterns_add_to_the_group(){
+        let manifest = Manifest{ ignore: vec![String::from("*.log")], ..Manifest::default() };
+        let config = Config{ ignore: vec![String::from("tmp")], ..Config::parse("home_dir/.config/app_1") };
+        let ignore = Ignore::new(&manifest, Some(&config)).unwrap();
+
+        assert!(ignore.matches(Path::new("a.log")));
+        assert!(ignore.matches(Path::new("tmp/a")));
+        assert!(!Ignore::new(&manifest, None).unwrap().matches(Path::new("tmp/a")));
+    }
+
+    #[test]
+    fn invalid_patterns(){
+        let manifest = Manifest{ ignore: vec![String::from("[a")], ..Manifest::default() };
+
+        assert!(Ignore::new(&manifest, None).is_err());
+    }
+
+    #[test]
+    fn nothing_ignored_by_default(){
+        assert!(!Ignore::default().matches(Path::new("a/b")));
+    }
+}
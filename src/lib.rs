@@ -0,0 +1,23 @@
+pub mod error;
+pub mod util;
+pub mod backend;
+pub mod flatpak;
+pub mod manifest;
+mod group;
+mod sync;
+mod diff;
+mod backup;
+pub mod state;
+mod status;
+pub mod template;
+pub mod secret;
+mod attributes;
+mod ignore;
+
+pub use error::AlpsError;
+pub use manifest::Manifest;
+pub use group::{Store, Group, EXCLUDES};
+pub use sync::{Phase, SyncOptions, Outcome, SyncEntry, SyncReport};
+pub use diff::{Change, ConfigDiff, FileDiff};
+pub use backup::{Snapshot, Restore};
+pub use status::{Status, StatusEntry};
@@ -1,141 +1,233 @@
 mod sift;
-mod util;
 mod flag;
 
 use std::{
-    fs,
     env, 
     collections::HashSet, 
 }; 
 use colored::Colorize;
+use alps::{AlpsError, Store, Phase};
 
-fn install(flags: HashSet<String>, args: Vec<String>, home_dir: &str){
-    sift::missing_flag(&flags);
+fn install(flags: HashSet<String>, args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    sift::missing_flag(&flags)?;
 
     if flags.contains("h") || flags.contains("--help"){
         flag::install_help();
+        Ok(())
     } 
     else if flags.contains("g") || flags.contains("--group"){
-        flag::install_group(args, home_dir);
+        flag::install_group(args, store)
     }
     else if flags.contains("p") || flags.contains("--package"){ 
-        flag::install_package(args, home_dir);
+        flag::install_package(args, store)
     }
     else if flags.contains("c") || flags.contains("--config"){
-        flag::install_config(args, home_dir);
+        flag::install_config(args, store, flags.contains("--symlink"), flags.contains("--template"), flags.contains("--secret"))
     }
     else if flags.contains("s") || flags.contains("--script"){
-        flag::install_script(args, home_dir);
+        flag::install_script(args, store)
+    }
+    else if flags.contains("f") || flags.contains("--flatpak"){
+        flag::install_flatpak(args, store)
+    }
+    else if flags.contains("t") || flags.contains("--tool"){
+        flag::install_tool(args, store)
+    }
+    else if flags.contains("b") || flags.contains("--backend"){
+        flag::install_backend(args, store)
+    }
+    else if flags.contains("i") || flags.contains("--include"){
+        flag::install_include(args, store)
     }
     else{
-        sift::invalid_flag();
+        sift::invalid_flag()
     }
 }
 
-fn remove(flags: HashSet<String>, args: Vec<String>, home_dir: &str){
-    sift::missing_flag(&flags);
+fn remove(flags: HashSet<String>, args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    sift::missing_flag(&flags)?;
 
     if flags.contains("h") || flags.contains("--help"){
         flag::remove_help();
+        Ok(())
     }
     else if flags.contains("g") || flags.contains("--group"){
-        flag::remove_group(args, home_dir);
+        flag::remove_group(args, store)
     }
     else if flags.contains("c") || flags.contains("--config"){
-        flag::remove_config(args, home_dir);
+        flag::remove_config(args, store)
     }
     else if flags.contains("s") || flags.contains("--script"){
-        flag::remove_script(args, home_dir);
+        flag::remove_script(args, store)
     }
     else if flags.contains("p") || flags.contains("--package"){
-        flag::remove_package(args, home_dir);
+        flag::remove_package(args, store, flags.contains("u") || flags.contains("--uninstall"))
+    }
+    else if flags.contains("f") || flags.contains("--flatpak"){
+        flag::remove_flatpak(args, store, flags.contains("u") || flags.contains("--uninstall"))
+    }
+    else if flags.contains("t") || flags.contains("--tool"){
+        flag::remove_tool(args, store, flags.contains("u") || flags.contains("--uninstall"))
+    }
+    else if flags.contains("b") || flags.contains("--backend"){
+        flag::remove_backend(args, store)
+    }
+    else if flags.contains("i") || flags.contains("--include"){
+        flag::remove_include(args, store)
     }
     else{
-        sift::invalid_flag();
+        sift::invalid_flag()
     }
 }
 
-fn sync(flags: HashSet<String>, mut args: Vec<String>, home_dir: &str){
-    sift::missing_flag(&flags);
+fn sync(flags: HashSet<String>, mut args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    sift::missing_flag(&flags)?;
+    let dry_run = flags.contains("--dry-run");
 
     if flags.contains("h") || flags.contains("--help"){
         flag::sync_help();
+        Ok(())
     }
     else if flags.contains("g") || flags.contains("--group"){
-        let mut group = String::new();
-        sift::missing_group(home_dir, &mut args, &mut group);
+        let group = sift::missing_group(store, &mut args)?;
 
-        flag::sync_group(home_dir, &group);
+        flag::sync_group(&group, dry_run)
     }
     else if flags.contains("p") || flags.contains("--package"){
-        let mut group = String::new();
-        sift::missing_group(home_dir, &mut args, &mut group);
+        let group = sift::missing_group(store, &mut args)?;
 
-        flag::sync_package(home_dir, &group);
+        flag::sync_phase(&group, Phase::Packages, dry_run)
     }
     else if flags.contains("c") || flags.contains("--config"){
-        let mut group = String::new();
-        sift::missing_group(home_dir, &mut args, &mut group);
+        let group = sift::missing_group(store, &mut args)?;
 
-        flag::sync_config(home_dir, &group);
+        flag::sync_phase(&group, Phase::Configs, dry_run)
     }
     else if flags.contains("s") || flags.contains("--script"){
-        let mut group = String::new();
-        sift::missing_group(home_dir, &mut args, &mut group);
+        let group = sift::missing_group(store, &mut args)?;
 
-        flag::sync_script(home_dir, &group);
+        flag::sync_phase(&group, Phase::Scripts, dry_run)
+    }
+    else if flags.contains("f") || flags.contains("--flatpak"){
+        let group = sift::missing_group(store, &mut args)?;
+
+        flag::sync_phase(&group, Phase::Flatpaks, dry_run)
+    }
+    else if flags.contains("t") || flags.contains("--tool"){
+        let group = sift::missing_group(store, &mut args)?;
+
+        flag::sync_phase(&group, Phase::Tools, dry_run)
     }
     else{
-        sift::invalid_flag();
+        sift::invalid_flag()
     }
 }
 
-fn query(flags: HashSet<String>, args: Vec<String>, home_dir: &str){
-    sift::missing_flag(&flags);
+fn query(flags: HashSet<String>, args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    sift::missing_flag(&flags)?;
 
     if flags.contains("h") || flags.contains("--help"){
         flag::query_help();
+        Ok(())
+    }
+    else if flags.contains("--status"){
+        flag::query_status(args, store)
     }
     else if flags.contains("g") || flags.contains("--group"){
-        flag::query_group(args, home_dir);
+        flag::query_group(args, store)
     }
     else if flags.contains("p") || flags.contains("--package"){
-        flag::query_package(args, home_dir);
+        flag::query_package(args, store)
     }
     else if flags.contains("c") || flags.contains("--config"){
-        flag::query_config(args, home_dir);
+        flag::query_config(args, store)
     }
     else if flags.contains("s") || flags.contains("--script"){
-        flag::query_script(args, home_dir);
+        flag::query_script(args, store)
+    }
+    else if flags.contains("f") || flags.contains("--flatpak"){
+        flag::query_flatpak(args, store)
+    }
+    else if flags.contains("t") || flags.contains("--tool"){
+        flag::query_tool(args, store)
+    }
+    else if flags.contains("b") || flags.contains("--backend"){
+        flag::query_backend(args, store)
+    }
+    else if flags.contains("i") || flags.contains("--include"){
+        flag::query_include(args, store)
+    }
+    else{
+        sift::invalid_flag()
+    }
+}
+
+fn diff(flags: HashSet<String>, args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    sift::missing_flag(&flags)?;
+
+    if flags.contains("h") || flags.contains("--help"){
+        flag::diff_help();
+        Ok(())
+    }
+    else if flags.contains("c") || flags.contains("--config"){
+        flag::diff_config(args, store)
     }
     else{
-        sift::invalid_flag();
+        sift::invalid_flag()
     }
 }
 
-fn edit(flags: HashSet<String>, args: Vec<String>, home_dir: &str){
+fn update(flags: HashSet<String>, args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    sift::missing_flag(&flags)?;
+
+    if flags.contains("h") || flags.contains("--help"){
+        flag::update_help();
+        Ok(())
+    }
+    else if flags.contains("c") || flags.contains("--config"){
+        flag::update_config(args, store)
+    }
+    else{
+        sift::invalid_flag()
+    }
+}
+
+fn rollback(flags: HashSet<String>, args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
+    if flags.contains("h") || flags.contains("--help"){
+        flag::rollback_help(store)
+    }
+    else if !flags.is_empty(){
+        sift::invalid_flag()
+    }
+    else{
+        flag::rollback(args, store)
+    }
+}
+
+fn edit(flags: HashSet<String>, args: Vec<String>, store: &Store)-> Result<(), AlpsError>{
     let mut editor = String::new();
-    sift::missing_flag(&flags);
-    sift::missing_editor(&mut editor);
+    sift::missing_flag(&flags)?;
+    sift::missing_editor(&mut editor)?;
 
     if flags.contains("h") || flags.contains("--help"){
         flag::edit_help();
+        Ok(())
     }   
     else if flags.contains("g") || flags.contains("--group"){
-        flag::edit_group(args, home_dir, editor);
+        flag::edit_group(args, store, editor)
     }
     else if flags.contains("c") || flags.contains("--config"){
-        flag::edit_config(args, home_dir, editor);
+        flag::edit_config(args, store, editor)
     }
     else if flags.contains("s") || flags.contains("--script"){
-        flag::edit_script(args, home_dir, editor);
+        flag::edit_script(args, store, editor)
     }
     else{
-        sift::invalid_flag();
+        sift::invalid_flag()
     }
 }
 
-fn parser(home_dir: &str){
+fn parser(store: &Store)-> Result<(), AlpsError>{
     let p_args: Vec<_> = env::args().collect();
 
     let mut args: Vec<String> = Vec::new();
@@ -148,13 +240,13 @@ fn parser(home_dir: &str){
             if arg.len() > 1 && &arg[..2] == "--"{
                 if arg.len() > 2{
                     match arg.as_str(){
-                        "--install" | "--remove" | "--sync" | "--query" | "--edit" =>{
-                            sift::duplicate_operation(&mut mode, arg.to_owned());
+                        "--install" | "--remove" | "--sync" | "--query" | "--edit" | "--diff" | "--update" | "--rollback" =>{
+                            sift::duplicate_operation(&mut mode, arg.to_owned())?;
                         }
-                        "--help" | "--group" | "--package" | "--config" | "--script" =>{
+                        "--help" | "--group" | "--package" | "--config" | "--script" | "--flatpak" | "--tool" | "--backend" | "--include" | "--uninstall" | "--dry-run" | "--symlink" | "--template" | "--secret" | "--status" =>{
                             flags.insert(arg.to_owned());
                         }
-                        arg => sift::invalid_operation(arg),
+                        arg => sift::invalid_operation(arg)?,
                     }
                 }
             }
@@ -163,13 +255,13 @@ fn parser(home_dir: &str){
                     let flag = flag.to_string();
 
                     match flag.as_str(){
-                        "I" | "R" | "S" | "Q" | "E" =>{
-                            sift::duplicate_operation(&mut mode, flag);
+                        "I" | "R" | "S" | "Q" | "E" | "D" | "U" =>{
+                            sift::duplicate_operation(&mut mode, flag)?;
                         }
-                        "h" | "p" | "c" | "g" | "s" =>{
+                        "h" | "p" | "c" | "g" | "s" | "f" | "t" | "b" | "i" | "u" =>{
                             flags.insert(flag);
                         }
-                        flag => sift::invalid_operation(flag),
+                        flag => sift::invalid_operation(flag)?,
                     }
                 }           
             }
@@ -190,46 +282,40 @@ fn parser(home_dir: &str){
             }
         }
     
-        match mode{
-            Some(flag) =>{
-                match flag.as_str(){
-                    "I" | "--install" => install(flags, args, home_dir), 
-                    "R" | "--remove" => remove(flags, args, home_dir),
-                    "S" | "--sync" => sync(flags, args, home_dir),
-                    "Q" | "--query" => query(flags, args, home_dir),
-                    "E" | "--edit" => edit(flags, args, home_dir),
-                    _ => (),
-                }
-            }
-            None =>{
-                eprintln!(
-                    "{} Expected operation!",
-                    "[!!!]".red()
-                );
-                util::help_menu();
-    
-                std::process::exit(1);
-            }
+        match mode.as_deref(){
+            Some("I" | "--install") => install(flags, args, store), 
+            Some("R" | "--remove") => remove(flags, args, store),
+            Some("S" | "--sync") => sync(flags, args, store),
+            Some("Q" | "--query") => query(flags, args, store),
+            Some("E" | "--edit") => edit(flags, args, store),
+            Some("D" | "--diff") => diff(flags, args, store),
+            Some("U" | "--update") => update(flags, args, store),
+            Some("--rollback") => rollback(flags, args, store),
+            _ => Err(AlpsError::Usage("Expected operation!".to_owned())),
         }
     }
     else{
-        eprintln!(
-            "{} Expected arguments!",
-            "[!!!]".red()
-        );
-        util::help_menu();
-
-        std::process::exit(1);       
+        Err(AlpsError::Usage("Expected arguments!".to_owned()))
     }
 }
 
+fn run()-> Result<(), AlpsError>{
+    let store = Store::open()?;
+    parser(&store)
+}
+
 fn main(){
-    let home_dir = dirs::home_dir()
-        .unwrap()
-        .into_os_string()
-        .into_string()
-        .unwrap() + "/.config/alps/";
-
-    fs::create_dir_all(&home_dir);
-    parser(&home_dir);
+    if let Err(error) = run(){
+        eprintln!(
+            "{} {}",
+            "[!!!]".red(),
+            error
+        );
+
+        if let AlpsError::Usage(_) = error{
+            flag::help_menu();
+        }
+
+        std::process::exit(error.exit_code());
+    }
 }
@@ -0,0 +1,792 @@
+use std::{
+    fs,
+    collections::BTreeMap,
+    io::ErrorKind,
+};
+use serde::{Serialize, Deserialize};
+use crate::util;
+use crate::backend;
+use crate::error::AlpsError;
+
+pub const VERSION: u32 = 1;
+pub const FILE: &str = "alps.toml";
+// Extra runs of a failing script with the retry policy, unless it sets its own.
+pub const RETRIES: u32 = 2;
+
+// Sections of the old <group>.conf format. Entries are still addressed by these labels,
+// rendered to and parsed from the one line form they had there.
+pub const LABELS: [&str; 9] = [
+    "[PACKAGES]",
+    "[FLATPAKS]",
+    "[CARGO]",
+    "[PIPX]",
+    "[NPM]",
+    "[CONFIGS]",
+    "[SCRIPTS]",
+    "[BACKEND]",
+    "[FLATPAK_REMOTES]"
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest{
+    pub version: u32,
+    // Groups synced along with this one, see Group::resolve.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub includes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    // How configs without a mode of their own are deployed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_mode: Option<Mode>,
+    // What happens when a script without a policy of its own fails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<OnFailure>,
+    // Globs left out of every directory config, see ignore::Ignore.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<Package>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flatpak_remotes: Vec<Remote>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flatpaks: Vec<Flatpak>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<Tool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub configs: Vec<Config>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scripts: Vec<Script>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Package{
+    pub name: String,
+    // Either aur, or the package manager the entry is limited to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Remote{
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Flatpak{
+    pub app: String,
+    pub remote: String,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub user: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tool{
+    pub tool: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode{
+    #[default]
+    Copy,
+    // A symlink pointing into <group>/configs/, so edits in place land in the group.
+    Symlink,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Config{
+    // Name of the copy under <group>/configs/, e.g. alacritty_1.
+    pub source: String,
+    // Where it is synced to, home_dir standing in for the user's home.
+    pub target: String,
+    // Falls back to the group's config_mode, then copy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<Mode>,
+    // Rendered with the host's variables at sync time, see template::Vars.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub template: bool,
+    // Stored encrypted under <group>/configs/, see secret.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub secret: bool,
+    // Globs left out of this config on top of the group's.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    // What a plain copy loses, recorded when the config is added or updated.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<Attributes>,
+}
+
+// Mode, owner and group of a file below a config, or the target of a symlink found there.
+// Files with a usual mode that belong to the user aren't listed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attributes{
+    // Relative to the config, empty for the config itself.
+    pub path: String,
+    // Octal, e.g. 600.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Script{
+    pub name: String,
+    // Runs the script through this command instead of executing it directly, so it needs
+    // neither a shebang nor the exec bit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpreter: Option<String>,
+    // Passed to the script after its path.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    // Where it runs, home_dir standing in for the user's home. Otherwise where alps runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+    // Set on top of the environment alps runs in.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    // Scripts run from lowest to highest order, in manifest order when equal.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub order: i64,
+    // Scripts of the group that have to run before this one, whatever their order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<String>,
+    // Runs at this point of the sync instead of with the other scripts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hook: Option<Hook>,
+    // When a sync runs it again, going by the state of this machine.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run: Option<Run>,
+    // Falls back to the group's on_failure, then continue.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<OnFailure>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Hook{
+    PrePackages,
+    PostPackages,
+    PreConfigs,
+    PostConfigs,
+    // After the scripts phase, the last thing a sync does.
+    Final,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Run{
+    #[default]
+    Always,
+    // Until it succeeds once.
+    Once,
+    // Again whenever its contents change, or its last run failed.
+    OnChange,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnFailure{
+    #[default]
+    Continue,
+    // Skips every script that would run after it.
+    Abort,
+    // Runs it again, up to retries times.
+    Retry,
+}
+
+// Where scripts of a hook run during a sync, those without one running with the scripts phase.
+fn slot(hook: Option<Hook>)-> u8{
+    match hook{
+        Some(Hook::PrePackages) => 0,
+        Some(Hook::PostPackages) => 1,
+        Some(Hook::PreConfigs) => 2,
+        Some(Hook::PostConfigs) => 3,
+        None => 4,
+        Some(Hook::Final) => 5,
+    }
+}
+
+fn is_false(value: &bool)-> bool{
+    !*value
+}
+
+fn is_zero(value: &i64)-> bool{
+    *value == 0
+}
+
+impl Package{
+    // Entries read [backend:]name[@version], the same way tools do.
+    pub fn parse(entry: &str)-> Package{
+        let (backend, spec) = match entry.split_once(':'){
+            Some((backend, spec)) if backend == "aur" || backend::BACKENDS.contains(&backend) => (Some(backend.to_owned()), spec),
+            _ => (None, entry),
+        };
+        let (name, version) = backend::split_version(spec);
+        Package{ name: name.to_owned(), backend, version: version.map(|version| version.to_owned()) }
+    }
+
+    pub fn entry(&self)-> String{
+        let mut entry = match &self.backend{
+            Some(backend) => backend.to_owned() + ":" + &self.name,
+            None => self.name.to_owned(),
+        };
+        if let Some(version) = &self.version{
+            entry = entry + "@" + version;
+        }
+        entry
+    }
+}
+
+impl Hook{
+    pub fn name(&self)-> &'static str{
+        match self{
+            Hook::PrePackages => "pre-packages",
+            Hook::PostPackages => "post-packages",
+            Hook::PreConfigs => "pre-configs",
+            Hook::PostConfigs => "post-configs",
+            Hook::Final => "final",
+        }
+    }
+}
+
+impl Remote{
+    pub fn parse(entry: &str)-> Option<Remote>{
+        let (name, url) = entry.split_once(' ')?;
+        Some(Remote{ name: name.to_owned(), url: url.trim().to_owned() })
+    }
+
+    pub fn entry(&self)-> String{
+        self.name.to_owned() + " " + &self.url
+    }
+}
+
+impl Flatpak{
+    pub fn parse(entry: &str)-> Option<Flatpak>{
+        let flatpak = crate::flatpak::Flatpak::parse(entry)?;
+        Some(Flatpak{ app: flatpak.app.to_owned(), remote: flatpak.remote.to_owned(), user: flatpak.user })
+    }
+
+    pub fn entry(&self)-> String{
+        let scope = if self.user{ "user:" } else{ "" };
+        scope.to_owned() + &self.remote + ":" + &self.app
+    }
+}
+
+impl Tool{
+    pub fn parse(tool: &str, spec: &str)-> Tool{
+        let (name, version) = backend::split_version(spec);
+        Tool{ tool: tool.to_owned(), name: name.to_owned(), version: version.map(|version| version.to_owned()) }
+    }
+
+    // The name[@version] spec, without the tool.
+    pub fn spec(&self)-> String{
+        match &self.version{
+            Some(version) => self.name.to_owned() + "@" + version,
+            None => self.name.to_owned(),
+        }
+    }
+}
+
+impl Config{
+    // Entries read home_dir/.config/alacritty_1, the target with the _N of its copy.
+    pub fn parse(entry: &str)-> Config{
+        let source = entry.rsplit('/').next().unwrap_or(entry);
+        let target = match entry.rsplit_once('_'){
+            Some((target, index)) if !index.contains('/') && index.parse::<usize>().is_ok() => target,
+            _ => entry,
+        };
+
+        Config{ source: source.to_owned(), target: target.to_owned(), mode: None, template: false, secret: false, ignore: Vec::new(), attributes: Vec::new() }
+    }
+
+    // Picked by its stored name (alacritty_1) or the name it syncs to (alacritty).
+    pub fn matches(&self, name: &str)-> bool{
+        self.source == name || self.target.rsplit('/').next() == Some(name)
+    }
+
+    pub fn entry(&self)-> String{
+        match self.target.rsplit_once('/'){
+            Some((parent, _)) => parent.to_owned() + "/" + &self.source,
+            None => self.source.to_owned(),
+        }
+    }
+}
+
+impl Script{
+    pub fn parse(entry: &str)-> Script{
+        Script{
+            name: entry.to_owned(),
+            interpreter: None,
+            args: Vec::new(),
+            dir: None,
+            env: BTreeMap::new(),
+            order: 0,
+            after: Vec::new(),
+            hook: None,
+            run: None,
+            on_failure: None,
+            retries: None,
+        }
+    }
+}
+
+impl Default for Manifest{
+    fn default()-> Manifest{
+        Manifest{
+            version: VERSION,
+            includes: Vec::new(),
+            backend: None,
+            config_mode: None,
+            on_failure: None,
+            ignore: Vec::new(),
+            packages: Vec::new(),
+            flatpak_remotes: Vec::new(),
+            flatpaks: Vec::new(),
+            tools: Vec::new(),
+            configs: Vec::new(),
+            scripts: Vec::new(),
+        }
+    }
+}
+
+impl Manifest{
+    pub fn path(group: &str, home_dir: &str)-> String{
+        home_dir.to_owned() + group + "/" + FILE
+    }
+
+    // Reads <group>/alps.toml, migrating <group>.conf into it the first time.
+    pub fn load(group: &str, home_dir: &str)-> Result<Manifest, AlpsError>{
+        let path = Manifest::path(group, home_dir);
+
+        match fs::read_to_string(&path){
+            Ok(text) => Manifest::parse(&text, &path),
+            Err(error) if error.kind() == ErrorKind::NotFound => Manifest::migrate(group, home_dir),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn parse(text: &str, path: &str)-> Result<Manifest, AlpsError>{
+        let manifest: Manifest = toml::from_str(text)
+            .map_err(|error| AlpsError::invalid("manifest", &(path.to_owned() + ": " + error.message())))?;
+
+        if manifest.version > VERSION{
+            return Err(AlpsError::invalid("manifest version", &manifest.version.to_string()));
+        }
+        Ok(manifest)
+    }
+
+    // The old .conf is kept next to the manifest as <group>.conf.bak.
+    fn migrate(group: &str, home_dir: &str)-> Result<Manifest, AlpsError>{
+        let conf = home_dir.to_owned() + group + "/" + group + ".conf";
+        let mut manifest = Manifest::default();
+
+        let text = match fs::read_to_string(&conf){
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(manifest),
+            Err(error) => return Err(error.into()),
+        };
+
+        for label in LABELS{
+            for entry in util::get_entries(&legacy_label(&text, label)){
+                manifest.insert(label, entry)?;
+            }
+        }
+
+        manifest.save(group, home_dir)?;
+        fs::rename(&conf, conf.to_owned() + ".bak")?;
+
+        Ok(manifest)
+    }
+
+    pub fn save(&self, group: &str, home_dir: &str)-> Result<(), AlpsError>{
+        let text = toml::to_string_pretty(self)
+            .map_err(|error| AlpsError::invalid("manifest", &error.to_string()))?;
+
+        fs::write(Manifest::path(group, home_dir), text)?;
+        Ok(())
+    }
+
+    pub fn mode(&self, config: &Config)-> Mode{
+        config.mode.or(self.config_mode).unwrap_or_default()
+    }
+
+    pub fn on_failure(&self, script: &Script)-> OnFailure{
+        script.on_failure.or(self.on_failure).unwrap_or_default()
+    }
+
+    // Scripts in the order they run, lowest order first and in manifest order when equal,
+    // except that each one waits for the scripts in its after list. Hooks split them up
+    // further, see sync::run, so a script can't wait for one hooked to a later point.
+    pub fn script_order(&self)-> Result<Vec<&Script>, AlpsError>{
+        let mut scripts = self.scripts.iter().collect::<Vec<&Script>>();
+        scripts.sort_by_key(|script| script.order);
+
+        let mut ordered = Vec::new();
+        for script in scripts{
+            self.order_into(script, &mut Vec::new(), &mut ordered)?;
+        }
+        Ok(ordered)
+    }
+
+    fn order_into<'a>(&'a self, script: &'a Script, stack: &mut Vec<&'a str>, ordered: &mut Vec<&'a Script>)-> Result<(), AlpsError>{
+        if let Some(start) = stack.iter().position(|name| *name == script.name){
+            let cycle = stack[start..].join(" -> ") + " -> " + &script.name;
+            return Err(AlpsError::invalid("script cycle", &cycle));
+        }
+        else if ordered.iter().any(|placed| placed.name == script.name){
+            return Ok(());
+        }
+
+        stack.push(&script.name);
+
+        for name in &script.after{
+            let after = self.scripts.iter()
+                .find(|after| after.name == *name)
+                .ok_or_else(|| AlpsError::invalid("script dependency", &(script.name.to_owned() + " after " + name)))?;
+
+            // Scripts of an earlier hook have run by then anyway, those of a later one can't.
+            if slot(after.hook) > slot(script.hook){
+                return Err(AlpsError::invalid("script dependency", &(script.name.to_owned() + " after " + name + ", which runs later")));
+            }
+            else if slot(after.hook) == slot(script.hook){
+                self.order_into(after, stack, ordered)?;
+            }
+        }
+
+        stack.pop();
+        ordered.push(script);
+
+        Ok(())
+    }
+
+    // Entries of a section in their one line form.
+    pub fn entries(&self, label: &str)-> Vec<String>{
+        match label{
+            "[PACKAGES]" => self.packages.iter().map(Package::entry).collect(),
+            "[FLATPAKS]" => self.flatpaks.iter().map(Flatpak::entry).collect(),
+            "[CONFIGS]" => self.configs.iter().map(Config::entry).collect(),
+            "[SCRIPTS]" => self.scripts.iter().map(|script| script.name.to_owned()).collect(),
+            "[BACKEND]" => self.backend.iter().cloned().collect(),
+            "[FLATPAK_REMOTES]" => self.flatpak_remotes.iter().map(Remote::entry).collect(),
+            label =>{
+                match tool_of(label){
+                    Some(tool) =>{
+                        self.tools.iter()
+                            .filter(|entry| entry.tool == tool)
+                            .map(Tool::spec)
+                            .collect()
+                    }
+                    None => Vec::new(),
+                }
+            }
+        }
+    }
+
+    pub fn insert(&mut self, label: &str, entry: &str)-> Result<(), AlpsError>{
+        match label{
+            "[PACKAGES]" => self.packages.push(Package::parse(entry)),
+            "[FLATPAKS]" =>{
+                let flatpak = Flatpak::parse(entry).ok_or_else(|| AlpsError::invalid("flatpak", entry))?;
+                self.flatpaks.push(flatpak);
+            }
+            "[CONFIGS]" => self.configs.push(Config::parse(entry)),
+            "[SCRIPTS]" => self.scripts.push(Script::parse(entry)),
+            "[BACKEND]" => self.backend = Some(entry.to_owned()),
+            "[FLATPAK_REMOTES]" =>{
+                let remote = Remote::parse(entry).ok_or_else(|| AlpsError::invalid("flatpak remote", entry))?;
+                self.flatpak_remotes.push(remote);
+            }
+            label =>{
+                let tool = tool_of(label).ok_or_else(|| AlpsError::invalid("label", label))?;
+                self.tools.push(Tool::parse(tool, entry));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, label: &str, entry: &str){
+        match label{
+            "[PACKAGES]" => self.packages.retain(|package| package.entry() != entry),
+            "[FLATPAKS]" => self.flatpaks.retain(|flatpak| flatpak.entry() != entry),
+            "[CONFIGS]" => self.configs.retain(|config| config.entry() != entry),
+            "[SCRIPTS]" =>{
+                self.scripts.retain(|script| script.name != entry);
+                for script in &mut self.scripts{
+                    script.after.retain(|name| name != entry);
+                }
+            }
+            "[BACKEND]" =>{
+                if self.backend.as_deref() == Some(entry){
+                    self.backend = None;
+                }
+            }
+            "[FLATPAK_REMOTES]" => self.flatpak_remotes.retain(|remote| remote.entry() != entry),
+            label =>{
+                if let Some(tool) = tool_of(label){
+                    self.tools.retain(|entry_tool| entry_tool.tool != tool || entry_tool.spec() != entry);
+                }
+            }
+        }
+    }
+}
+
+fn tool_of(label: &str)-> Option<&'static str>{
+    backend::TOOLS.iter()
+        .find(|(_, tool_label)| *tool_label == label)
+        .map(|(tool, _)| *tool)
+}
+
+fn legacy_label(text: &str, label: &str)-> String{
+    let text: Vec<&str> = text.split(label).collect();
+
+    if text.len() > 1{
+        let mut text = text[1];
+
+        for exclude in LABELS{
+            text = text.split(exclude).next().unwrap_or_default();
+        }
+
+        text.to_string()
+    }
+    else{
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+    use std::{env, process};
+
+    #[test]
+    fn config_round_trip(){
+        for entry in ["home_dir/.config/alacritty_1", "home_dir/.config/my_app_12", "/etc/pacman.conf_2"]{
+            assert_eq!(Config::parse(entry).entry(), entry);
+        }
+
+        let config = Config::parse("home_dir/.config/my_app_2");
+        assert_eq!(config.source, "my_app_2");
+        assert_eq!(config.target, "home_dir/.config/my_app");
+        assert!(config.matches("my_app") && config.matches("my_app_2"));
+        assert!(!config.matches("my"));
+    }
+
+    #[test]
+    fn config_without_index(){
+        let config = Config::parse("home_dir/.config/my_app");
+
+        assert_eq!(config.source, "my_app");
+        assert_eq!(config.target, "home_dir/.config/my_app");
+    }
+
+    #[test]
+    fn package_and_tool_round_trip(){
+        for entry in ["ripgrep", "aur:paru-bin", "@development-tools", "apt:build-essential", "dnf:gcc@13.2.1-1.fc39"]{
+            assert_eq!(Package::parse(entry).entry(), entry);
+        }
+
+        let package = Package::parse("ripgrep@14.1.0");
+        assert_eq!(package.name, "ripgrep");
+        assert_eq!(package.backend, None);
+        assert_eq!(package.version.as_deref(), Some("14.1.0"));
+
+        let tool = Tool::parse("npm", "@types/node@20.1.0");
+        assert_eq!(tool.name, "@types/node");
+        assert_eq!(tool.version.as_deref(), Some("20.1.0"));
+        assert_eq!(tool.spec(), "@types/node@20.1.0");
+    }
+
+    #[test]
+    fn legacy_labels(){
+        let text = "[PACKAGES]\nripgrep\n[FLATPAKS]\nflathub:org.gimp.GIMP\n[FLATPAK_REMOTES]\nfedora https://x\n";
+
+        assert_eq!(legacy_label(text, "[PACKAGES]"), "\nripgrep\n");
+        assert_eq!(legacy_label(text, "[FLATPAKS]"), "\nflathub:org.gimp.GIMP\n");
+        assert_eq!(legacy_label(text, "[FLATPAK_REMOTES]"), "\nfedora https://x\n");
+        assert_eq!(legacy_label(text, "[CONFIGS]"), "");
+    }
+
+    #[test]
+    fn conf_migration(){
+        let home_dir = env::temp_dir().join("alps-test-".to_owned() + &process::id().to_string()).display().to_string() + "/";
+        let conf = home_dir.to_owned() + "main/main.conf";
+
+        fs::create_dir_all(home_dir.to_owned() + "main").unwrap();
+        fs::write(
+            &conf,
+            "[PACKAGES]\nripgrep\naur:paru-bin\n[CONFIGS]\nhome_dir/.config/alacritty_1\n[SCRIPTS]\nsetup.sh\n[CARGO]\nbat@0.24.0\n[BACKEND]\npacman\n"
+        ).unwrap();
+
+        let manifest = Manifest::load("main", &home_dir).unwrap();
+        let reloaded = Manifest::load("main", &home_dir).unwrap();
+        let kept = fs::metadata(conf.to_owned() + ".bak").is_ok() && fs::metadata(&conf).is_err();
+        let _ = fs::remove_dir_all(&home_dir);
+
+        assert!(kept);
+        assert_eq!(manifest.entries("[PACKAGES]"), ["ripgrep", "aur:paru-bin"]);
+        assert_eq!(manifest.entries("[CONFIGS]"), ["home_dir/.config/alacritty_1"]);
+        assert_eq!(manifest.entries("[SCRIPTS]"), ["setup.sh"]);
+        assert_eq!(manifest.entries("[CARGO]"), ["bat@0.24.0"]);
+        assert_eq!(manifest.backend.as_deref(), Some("pacman"));
+        assert_eq!(reloaded.packages, manifest.packages);
+    }
+
+    fn manifest(text: &str)-> Manifest{
+        Manifest::parse(&("version = 1\n".to_owned() + text), FILE).unwrap()
+    }
+
+    fn order(manifest: &Manifest)-> Vec<&str>{
+        manifest.script_order()
+            .unwrap()
+            .into_iter()
+            .map(|script| script.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn scripts_by_order_then_manifest(){
+        let manifest = manifest(r#"
+            [[scripts]]
+            name = "c"
+            order = 1
+
+            [[scripts]]
+            name = "a"
+
+            [[scripts]]
+            name = "b"
+
+            [[scripts]]
+            name = "first"
+            order = -1
+        "#);
+
+        assert_eq!(order(&manifest), ["first", "a", "b", "c"]);
+    }
+
+    #[test]
+    fn scripts_wait_for_after(){
+        let manifest = manifest(r#"
+            [[scripts]]
+            name = "services"
+            after = ["rules", "units"]
+
+            [[scripts]]
+            name = "units"
+            after = ["rules"]
+
+            [[scripts]]
+            name = "rules"
+            order = 5
+        "#);
+
+        assert_eq!(order(&manifest), ["rules", "units", "services"]);
+    }
+
+    #[test]
+    fn script_cycles(){
+        let manifest = manifest(r#"
+            [[scripts]]
+            name = "a"
+            after = ["b"]
+
+            [[scripts]]
+            name = "b"
+            after = ["c"]
+
+            [[scripts]]
+            name = "c"
+            after = ["a"]
+        "#);
+
+        match manifest.script_order(){
+            Err(AlpsError::InvalidEntry{ kind, entry }) =>{
+                assert_eq!(kind, "script cycle");
+                assert_eq!(entry, "a -> b -> c -> a");
+            }
+            _ => panic!("cycle not refused"),
+        }
+    }
+
+    #[test]
+    fn script_after_itself(){
+        let manifest = manifest(r#"
+            [[scripts]]
+            name = "a"
+            after = ["a"]
+        "#);
+
+        assert!(manifest.script_order().is_err());
+    }
+
+    #[test]
+    fn script_after_missing(){
+        let manifest = manifest(r#"
+            [[scripts]]
+            name = "a"
+            after = ["gone"]
+        "#);
+
+        match manifest.script_order(){
+            Err(AlpsError::InvalidEntry{ kind, entry }) =>{
+                assert_eq!(kind, "script dependency");
+                assert_eq!(entry, "a after gone");
+            }
+            _ => panic!("missing dependency not refused"),
+        }
+    }
+
+    #[test]
+    fn script_after_hooks(){
+        let earlier = manifest(r#"
+            [[scripts]]
+            name = "a"
+            after = ["key"]
+
+            [[scripts]]
+            name = "key"
+            hook = "pre-packages"
+        "#);
+        let later = manifest(r#"
+            [[scripts]]
+            name = "a"
+            after = ["last"]
+
+            [[scripts]]
+            name = "last"
+            hook = "final"
+        "#);
+
+        assert_eq!(order(&earlier), ["a", "key"]);
+        assert!(later.script_order().is_err());
+    }
+
+    #[test]
+    fn removed_scripts_leave_after(){
+        let mut manifest = manifest(r#"
+            [[scripts]]
+            name = "a"
+            after = ["b"]
+
+            [[scripts]]
+            name = "b"
+        "#);
+        manifest.remove("[SCRIPTS]", "b");
+
+        assert!(manifest.scripts[0].after.is_empty());
+        assert_eq!(order(&manifest), ["a"]);
+    }
+}
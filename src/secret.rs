@@ -0,0 +1,88 @@
+use std::{
+    fs,
+    env,
+    path::Path,
+    sync::OnceLock,
+};
+use age::secrecy::SecretString;
+use age::scrypt::{Recipient, Identity};
+use crate::error::AlpsError;
+
+// Secret configs are stored encrypted with age under a passphrase, taken from this
+// variable or asked for once per run.
+pub const VAR: &str = "ALPS_PASSPHRASE";
+
+// Both run a benchmark to pick the scrypt work factor, so they're made once per run.
+struct Keys{
+    identity: Identity,
+    recipient: Recipient,
+}
+
+static KEYS: OnceLock<Keys> = OnceLock::new();
+
+// A passphrase typed to encrypt is asked for twice, since a typo would leave the secret
+// unreadable. Decrypting already tells a wrong one apart.
+fn keys(confirm: bool)-> Result<&'static Keys, AlpsError>{
+    if let Some(keys) = KEYS.get(){
+        return Ok(keys);
+    }
+
+    let passphrase = match env::var(VAR){
+        Ok(passphrase) => passphrase,
+        Err(_) =>{
+            let passphrase = rpassword::prompt_password("Passphrase for secret configs: ")?;
+
+            if confirm && !passphrase.is_empty() && rpassword::prompt_password("Confirm passphrase: ")? != passphrase{
+                return Err(AlpsError::invalid("passphrase", "does not match"));
+            }
+            passphrase
+        }
+    };
+
+    if passphrase.is_empty(){
+        return Err(AlpsError::invalid("passphrase", "empty"));
+    }
+
+    Ok(KEYS.get_or_init(||{
+        Keys{
+            identity: Identity::new(SecretString::from(passphrase.to_owned())),
+            recipient: Recipient::new(SecretString::from(passphrase)),
+        }
+    }))
+}
+
+// Whether secrets can be read without asking, so commands that only look at the system
+// never prompt for the passphrase.
+pub fn unlocked()-> bool{
+    KEYS.get().is_some() || env::var(VAR).is_ok()
+}
+
+// Gets the passphrase before anything is touched, e.g. when updating a secret config.
+pub(crate) fn unlock()-> Result<(), AlpsError>{
+    keys(false).map(|_| ())
+}
+
+// Reads and decrypts a stored file.
+pub fn read(path: &Path)-> Result<Vec<u8>, AlpsError>{
+    let contents = fs::read(path)?;
+
+    age::decrypt(&keys(false)?.identity, &contents)
+        .map_err(|error| AlpsError::invalid("secret", &(path.display().to_string() + ": " + &error.to_string())))
+}
+
+pub fn write(path: &Path, contents: &[u8])-> Result<(), AlpsError>{
+    let contents = age::encrypt(&keys(true)?.recipient, contents)
+        .map_err(|error| AlpsError::invalid("secret", &(path.display().to_string() + ": " + &error.to_string())))?;
+
+    fs::write(path, contents)?;
+    Ok(())
+}
+
+// Like util::copy_dir into the group, encrypting the file on the way so its plain
+// contents never land there.
+pub(crate) fn copy_file(src: &Path, dst: &Path)-> Result<(), AlpsError>{
+    if let Some(parent) = dst.parent(){
+        fs::create_dir_all(parent)?;
+    }
+    write(dst, &fs::read(src)?)
+}
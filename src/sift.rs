@@ -3,127 +3,94 @@ use std::{
     fs,
     env,
     path::{Path, PathBuf}, 
-    process::Command,
     collections::HashSet, 
 }; 
-use crate::util;
+use alps::{util, AlpsError, Store, Group, Manifest, EXCLUDES};
+use alps::backend::{self, PackageBackend};
+use alps::flatpak::{self, Flatpak};
+use alps::manifest::Package;
 
-pub fn invalid_operation(operation: &str){
+pub fn invalid_operation(operation: &str)-> Result<(), AlpsError>{
     let hyphen = 
         if operation.len() == 1{ "-" }
         else { "" };
 
-    eprintln!(
-        "{} Invalid operation ({}{})!",
-        "[!!!]".red(),
-        hyphen.red(),
-        operation.red()
-    );
-    util::help_menu();
-
-    std::process::exit(1);
+    Err(AlpsError::Usage(format!("Invalid operation ({hyphen}{operation})!")))
 }
 
-pub fn duplicate_operation(mode: &mut Option<String>, flag: String){
-    if *mode == None{
+pub fn duplicate_operation(mode: &mut Option<String>, flag: String)-> Result<(), AlpsError>{
+    if mode.is_none(){
        *mode = Some(flag); 
+       Ok(())
     }
     else{
-        eprintln!(
-            "{} Cannot use more than one operation!",
-            "[!!!]".red()
-        );
-
-        std::process::exit(1);
+        Err(AlpsError::Usage(String::from("Cannot use more than one operation!")))
     }
 }
 
-pub fn invalid_flag(){
-    eprintln!(
-        "{} Invalid flag! (use -h for help)",
-        "[!!!]".red()
-    );
-    
-    std::process::exit(1);
+pub fn invalid_flag()-> Result<(), AlpsError>{
+    Err(AlpsError::Usage(String::from("Invalid flag!")))
 }
 
-pub fn missing_editor(editor: &mut String){
+pub fn missing_editor(editor: &mut String)-> Result<(), AlpsError>{
     match env::var("EDITOR"){
-        Ok(extract) => *editor = extract,
-        Err(_) =>{
-            eprintln!(
-                "{} Editor not found! Set environment variable EDITOR to continue...",
-                "[!!!]".red()
-            );
-            std::process::exit(1);
-        } 
+        Ok(extract) =>{
+            *editor = extract;
+            Ok(())
+        }
+        Err(_) => Err(AlpsError::Usage(String::from("Editor not found! Set environment variable EDITOR to continue..."))),
     }
 }
 
-pub fn missing_group(home_dir: &str, args: &mut Vec<String>, group: &mut String){
-    let excludes = vec![
-        String::from(".git"), 
-        String::from(".."), 
-        String::from(".")
-    ];
-
+pub fn missing_group(store: &Store, args: &mut Vec<String>)-> Result<Group, AlpsError>{
     if args.is_empty(){
-        eprintln!(
-            "{} Expected group! (use -h for help)",
-            "[!!!]".red()
-        );
-        std::process::exit(1);
+        return Err(AlpsError::MissingGroup(String::new()));
     }
 
-    if !Path::new(&(home_dir.to_owned() + &args[0])).is_dir() || excludes.contains(&args[0]){
-        eprintln!(
-            "{} Invalid group ({})! (use -h for help)",
-            "[!!!]".red(),
-            &args[0].red()
-        );
-        std::process::exit(1);
-    }
-    *group = args[0].clone();
+    let group = store.group(&args[0])?;
     args.remove(0);
+
+    Ok(group)
 }
 
-pub fn missing_flag(flags: &HashSet<String>){
+pub fn missing_flag(flags: &HashSet<String>)-> Result<(), AlpsError>{
     if flags.is_empty(){
-        eprintln!(
-            "{} Expected flag! (use -h for help)",
-            "[!!!]".red()
-        );
-        std::process::exit(1);
+        Err(AlpsError::Usage(String::from("Expected flag!")))
+    }
+    else{
+        Ok(())
     }
 }
 
-pub fn missing_args(args: &mut Vec<String>, len: usize){
+pub fn missing_args(args: &[String], len: usize)-> Result<(), AlpsError>{
     if args.len() < len{
-        eprintln!(
-            "{} Expected arguments! (use -h for help)",
-            "[!!!]".red()
-        );
-        std::process::exit(1);
+        Err(AlpsError::Usage(String::from("Expected arguments!")))
+    }
+    else{
+        Ok(())
     }
 }
 
-pub fn invalid_groups(home_dir: &str, args: &mut Vec<String>, mode: bool){
-    let excludes = vec![
-        String::from(".git"), 
-        String::from(".."), 
-        String::from(".")
-    ];
+pub fn invalid_backend(name: &str)-> Result<(), AlpsError>{
+    if backend::from_name(name).is_none(){
+        Err(AlpsError::invalid("backend", name))
+    }
+    else{
+        Ok(())
+    }
+}
 
+pub fn invalid_groups(home_dir: &str, args: &mut Vec<String>, mode: bool){
     *args = args.clone()
         .into_iter()
         .filter_map(|group|{
             let exists = Path::new(&(home_dir.to_owned() + &group)).is_dir();
 
-            if excludes.contains(&group){
+            if EXCLUDES.contains(&group.as_str()){
                 eprintln!(
                     "{} Invalid group ({})! (use -h for help)",
                     "[!]".yellow(),
-                    &args[0].yellow()
+                    group.yellow()
                 );
                 None
             }
@@ -158,13 +125,17 @@ pub fn invalid_groups(home_dir: &str, args: &mut Vec<String>, mode: bool){
     }).collect();
 }
 
-pub fn invalid_packages(home_dir: &str, args: &mut Vec<String>, mode: bool, group: &str){
+pub fn invalid_packages(home_dir: &str, args: &mut Vec<String>, mode: bool, group: &str)-> Result<(), AlpsError>{
     println!(
         "{} Processing arguments. Please wait...",
         "[*]".magenta()
     );
 
     let mut passed = HashSet::new();
+    let text = util::read_label("[PACKAGES]", group, home_dir)?;
+    let backend = 
+        if mode{ Some(backend::select(group, home_dir)?) }
+        else{ None };
 
     *args = args.clone()
         .into_iter()
@@ -180,11 +151,20 @@ pub fn invalid_packages(home_dir: &str, args: &mut Vec<String>, mode: bool, grou
             else{
                 passed.insert(package.to_owned());
 
-                let exists = util::get_entries(&util::read_label("[PACKAGES]", group, home_dir))
-                    .any(|entry| package == entry);
-    
+                let parsed = Package::parse(&package);
+                let name = parsed.name.as_str();
+                // A bare name stands for any entry of it, a backend or version only for that one.
+                let bare = parsed.backend.is_none() && parsed.version.is_none();
+                let matches = util::get_entries(&text)
+                    .filter(|entry| *entry == package || (bare && Package::parse(entry).name == name))
+                    .map(|entry| entry.to_owned())
+                    .collect::<Vec<String>>();
+
                 if mode{
-                    if exists{
+                    let installed = matches.iter()
+                        .any(|entry| *entry == package || Package::parse(entry).backend == parsed.backend);
+
+                    if installed{
                         eprintln!(
                             "{} Package ({}) already installed to group!",
                             "[!]".yellow(),
@@ -192,49 +172,298 @@ pub fn invalid_packages(home_dir: &str, args: &mut Vec<String>, mode: bool, grou
                         );
                         None
                     }
+                    else if parsed.backend.as_deref() == Some("aur"){
+                        match backend::aur_helper(){
+                            Some(aur) if aur.exists(name) => Some(package),
+                            Some(_) =>{
+                                eprintln!(
+                                    "{} Package ({}) does not exist in AUR!",
+                                    "[!]".yellow(),
+                                    name.yellow()
+                                );
+                                None
+                            }
+                            None =>{
+                                eprintln!(
+                                    "{} No AUR helper found for package ({})! (install one of {} or set ALPS_AUR_HELPER)",
+                                    "[!]".yellow(),
+                                    name.yellow(),
+                                    backend::AUR_HELPERS.join(", ")
+                                );
+                                None
+                            }
+                        }
+                    }
+                    // Packages for another package manager can't be looked up from this machine.
+                    else if backend.as_ref().is_some_and(|backend|{
+                        parsed.backend.as_ref().is_some_and(|prefix| backend.name() != prefix) || backend.exists(name)
+                    }){
+                        Some(package)
+                    }
+                    else if bare && backend::aur_helper().is_some_and(|aur| aur.exists(name)){
+                        println!(
+                            "{} Package ({}) found in AUR...",
+                            "[*]".magenta(),
+                            package.magenta()
+                        );
+                        Some("aur:".to_owned() + &package)
+                    }
                     else{
-                        let handle = Command::new("pacman")
-                            .args(["-Ss", &("^".to_owned() + &package + "$")])
-                            .output()
-                            .unwrap();
-    
-                        if !handle.status.success(){ 
+                        eprintln!(
+                            "{} Package ({}) does not exist in repository!",
+                            "[!]".yellow(),
+                            package.yellow()
+                        );
+                        None
+                    }
+                }
+                else{
+                    match matches.as_slice(){
+                        [] =>{
                             eprintln!(
-                                "{} Package ({}) does not exist in repository!",
+                                "{} Package ({}) does not exist in group!",
                                 "[!]".yellow(),
                                 package.yellow()
                             );
                             None
                         }
-                        else{
-                            Some(package)
+                        [entry] => Some(entry.to_owned()),
+                        _ if matches.contains(&package) => Some(package),
+                        _ =>{
+                            eprintln!(
+                                "{} Package ({}) matches several entries ({})! Name the one to use...",
+                                "[!]".yellow(),
+                                package.yellow(),
+                                matches.join(", ").yellow()
+                            );
+                            None
                         }
                     }
                 }
-                else{
-                    if !exists{
+            }
+    }).collect();
+
+    Ok(())
+}
+
+pub fn invalid_flatpaks(home_dir: &str, args: &mut Vec<String>, mode: bool, group: &str)-> Result<(), AlpsError>{
+    println!(
+        "{} Processing arguments. Please wait...",
+        "[*]".magenta()
+    );
+
+    let mut passed = HashSet::new();
+    let text = util::get_entries(&util::read_label("[FLATPAKS]", group, home_dir)?)
+        .map(|entry| entry.to_owned())
+        .collect::<Vec<String>>();
+    let remotes = Manifest::load(group, home_dir)?.flatpak_remotes;
+    let declared = args.iter()
+        .filter_map(|arg| arg.split_once('='))
+        .map(|(remote, _)| remote.to_owned())
+        .collect::<HashSet<String>>();
+
+    *args = args.clone()
+        .into_iter()
+        .filter_map(|flatpak|{
+            if passed.contains(&flatpak){
+                eprintln!(
+                    "{} Removing duplicate argument ({})!",
+                    "[!]".yellow(),
+                    flatpak.yellow()
+                );
+                return None;
+            }
+            passed.insert(flatpak.to_owned());
+
+            if !mode{
+                let entry = text.iter()
+                    .find(|entry|{
+                        **entry == flatpak
+                            || Flatpak::parse(entry).is_some_and(|entry| entry.app == flatpak)
+                    })
+                    .cloned();
+
+                if entry.is_none(){
+                    eprintln!(
+                        "{} Flatpak ({}) does not exist in group!",
+                        "[!]".yellow(),
+                        flatpak.yellow()
+                    );
+                }
+                return entry;
+            }
+
+            if let Some((remote, url)) = flatpak.split_once('='){
+                if remote.is_empty() || url.is_empty() || remote.contains([' ', ':']){
+                    eprintln!(
+                        "{} Invalid flatpak remote ({})! Expected <remote>=<url>...",
+                        "[!]".yellow(),
+                        flatpak.yellow()
+                    );
+                    return None;
+                }
+
+                return Some(flatpak);
+            }
+
+            match Flatpak::parse(&flatpak){
+                Some(entry) =>{
+                    let exists = text.iter()
+                        .filter_map(|text| Flatpak::parse(text))
+                        .any(|text| text.app == entry.app && text.user == entry.user);
+
+                    if exists{
                         eprintln!(
-                            "{} Package ({}) does not exist in group!",
+                            "{} Flatpak ({}) already installed to group!",
                             "[!]".yellow(),
-                            package.yellow()
+                            flatpak.yellow()
+                        );
+                        None
+                    }
+                    else if !declared.contains(entry.remote)
+                        && flatpak::find_remote(entry.remote, &remotes).is_none()
+                    {
+                        eprintln!(
+                            "{} Flatpak remote ({}) is unknown to group! (add it with {}=<url>)",
+                            "[!]".yellow(),
+                            entry.remote.yellow(),
+                            entry.remote
+                        );
+                        None
+                    }
+                    else if !entry.exists(){
+                        eprintln!(
+                            "{} Flatpak ({}) does not exist in remote ({})!",
+                            "[!]".yellow(),
+                            entry.app.yellow(),
+                            entry.remote.yellow()
                         );
                         None
                     }
                     else{
-                        Some(package)
+                        Some(flatpak.to_owned())
                     }
                 }
+                None =>{
+                    eprintln!(
+                        "{} Invalid flatpak ({})! Expected [user:]<remote>:<app-id>...",
+                        "[!]".yellow(),
+                        flatpak.yellow()
+                    );
+                    None
+                }
+            }
+    }).collect();
+
+    Ok(())
+}
+
+pub fn invalid_tools(home_dir: &str, args: &mut Vec<String>, mode: bool, group: &str)-> Result<(), AlpsError>{
+    println!(
+        "{} Processing arguments. Please wait...",
+        "[*]".magenta()
+    );
+
+    let mut passed = HashSet::new();
+    let mut texts = Vec::new();
+
+    for (kind, label) in backend::TOOLS{
+        texts.push((kind, util::read_label(label, group, home_dir)?));
+    }
+
+    *args = args.clone()
+        .into_iter()
+        .filter_map(|tool|{
+            if passed.contains(&tool){
+                eprintln!(
+                    "{} Removing duplicate argument ({})!",
+                    "[!]".yellow(),
+                    tool.yellow()
+                );
+                return None;
+            }
+            passed.insert(tool.to_owned());
+
+            let (prefix, spec) = match tool.split_once(':'){
+                Some((prefix, spec)) if backend::from_tool(prefix).is_some() => (Some(prefix), spec),
+                _ => (None, tool.as_str()),
+            };
+            let name = backend::split_version(spec).0;
+
+            let entry = texts.iter()
+                .filter(|(kind, _)| prefix.is_none_or(|prefix| prefix == *kind))
+                .find_map(|(kind, text)|{
+                    util::get_entries(text)
+                        .find(|entry| *entry == spec || backend::split_version(entry).0 == name)
+                        .map(|entry| kind.to_string() + ":" + entry)
+                });
+
+            if !mode{
+                if entry.is_none(){
+                    eprintln!(
+                        "{} Tool ({}) does not exist in group!",
+                        "[!]".yellow(),
+                        tool.yellow()
+                    );
+                }
+                return entry;
+            }
+
+            let prefix = match prefix{
+                Some(prefix) => prefix,
+                None =>{
+                    eprintln!(
+                        "{} Invalid tool ({})! Expected <{}>:<name>[@version]...",
+                        "[!]".yellow(),
+                        tool.yellow(),
+                        backend::TOOLS.map(|(kind, _)| kind).join("|")
+                    );
+                    return None;
+                }
+            };
+            let backend = backend::from_tool(prefix).unwrap();
+
+            if entry.is_some(){
+                eprintln!(
+                    "{} Tool ({}) already installed to group!",
+                    "[!]".yellow(),
+                    tool.yellow()
+                );
+                None
+            }
+            else if !backend.available(){
+                eprintln!(
+                    "{} Command ({}) not found on system!",
+                    "[!]".yellow(),
+                    prefix.yellow()
+                );
+                None
+            }
+            else if !backend.exists(spec){
+                eprintln!(
+                    "{} Tool ({}) does not exist in registry of ({})!",
+                    "[!]".yellow(),
+                    spec.yellow(),
+                    prefix.yellow()
+                );
+                None
+            }
+            else{
+                Some(tool)
             }
     }).collect();
+
+    Ok(())
 }
 
-pub fn invalid_configs(home_dir: &str, args: &mut Vec<String>, mode: bool, group: &str){
+pub fn invalid_configs(home_dir: &str, args: &mut Vec<String>, mode: bool, group: &str)-> Result<(), AlpsError>{
     println!(
         "{} Processing arguments. Please wait...",
         "[*]".magenta()
     );
 
     let mut passed = HashSet::new();
+    let text = util::read_label("[CONFIGS]", group, home_dir)?;
 
     *args = args.clone()
         .into_iter()
@@ -242,9 +471,17 @@ pub fn invalid_configs(home_dir: &str, args: &mut Vec<String>, mode: bool, group
             if mode{
                 match fs::canonicalize(PathBuf::from(&config)){
                     Ok(true_path) =>{
-                        let true_path = true_path.into_os_string()
-                            .into_string()
-                            .unwrap();
+                        let true_path = match true_path.into_os_string().into_string(){
+                            Ok(true_path) => true_path,
+                            Err(_) =>{
+                                eprintln!(
+                                    "{} Path to config ({}) is not valid UTF-8!",
+                                    "[!]".yellow(),
+                                    config.yellow()
+                                );
+                                return None;
+                            }
+                        };
 
                         if passed.contains(&true_path){
                             eprintln!(
@@ -260,7 +497,7 @@ pub fn invalid_configs(home_dir: &str, args: &mut Vec<String>, mode: bool, group
                             let mut generic_path = true_path.to_owned();
                             util::to_template(&mut generic_path);
 
-                            let contains = util::get_entries(&util::read_label("[CONFIGS]", group, home_dir))
+                            let contains = util::get_entries(&text)
                                 .any(|entry| generic_path == entry.rsplit_once('_').unwrap_or((entry, entry)).0);
     
                             if contains{
@@ -289,10 +526,10 @@ pub fn invalid_configs(home_dir: &str, args: &mut Vec<String>, mode: bool, group
             }
             else{
                 let mut config_path = String::new();
-                let contains = util::get_entries(&util::read_label("[CONFIGS]", group, home_dir))
+                let contains = util::get_entries(&text)
                     .any(|entry|{
                         config_path = entry.to_string();
-                        config == entry.split('/').last().unwrap()
+                        config == entry.split('/').next_back().unwrap_or(entry)
                     });
 
                 if passed.contains(&config_path){
@@ -320,27 +557,27 @@ pub fn invalid_configs(home_dir: &str, args: &mut Vec<String>, mode: bool, group
                 }
             }
     }).collect();
+
+    Ok(())
 }
 
-pub fn invalid_scripts(home_dir: &str, args: &mut Vec<String>, mode: bool, group: &str){
+pub fn invalid_scripts(home_dir: &str, args: &mut Vec<String>, mode: bool, group: &str)-> Result<(), AlpsError>{
     println!(
         "{} Processing arguments. Please wait...",
         "[*]".magenta()
     );
 
     let mut passed = HashSet::new();
+    let text = util::read_label("[SCRIPTS]", group, home_dir)?;
 
     *args = args.clone()
         .into_iter()
         .filter_map(|script|{
             if mode{
-                let mut script_name = String::new();
+                let script_name = script.split('/').next_back().unwrap_or(&script).to_string();
 
-                let contains = util::get_entries(&util::read_label("[SCRIPTS]", group, home_dir))
-                    .any(|entry|{
-                        script_name = script.split('/').last().unwrap().to_string();
-                        script_name == entry
-                    });
+                let contains = util::get_entries(&text)
+                    .any(|entry| script_name == entry);
                 
                 if passed.contains(&script_name){
                     eprintln!(
@@ -401,7 +638,7 @@ pub fn invalid_scripts(home_dir: &str, args: &mut Vec<String>, mode: bool, group
                 else{
                     passed.insert(script.to_owned());
 
-                    let contains = util::get_entries(&util::read_label("[SCRIPTS]", group, home_dir))
+                    let contains = util::get_entries(&text)
                         .any(|entry| script == entry );
     
                     if !contains{
@@ -418,4 +655,6 @@ pub fn invalid_scripts(home_dir: &str, args: &mut Vec<String>, mode: bool, group
                 }
             }
     }).collect();
+
+    Ok(())
 }
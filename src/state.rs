@@ -0,0 +1,66 @@
+use std::{
+    fs,
+    collections::BTreeMap,
+    io::ErrorKind,
+};
+use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
+use crate::error::AlpsError;
+
+// What happened on this machine, kept apart from the shared group folders under
+// <state_dir>/groups/<group>.toml.
+pub const DIR: &str = "groups";
+// Where older versions kept it, inside the store.
+pub const LEGACY_DIR: &str = ".state";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct State{
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub scripts: BTreeMap<String, ScriptRun>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptRun{
+    pub succeeded: bool,
+    // When it last ran, as YYYYMMDD-HHMMSS in UTC.
+    pub ran: String,
+    // sha256 of the script it ran, see manifest::Run::OnChange.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+impl State{
+    pub fn path(group: &str, state_dir: &str)-> String{
+        state_dir.to_owned() + DIR + "/" + group + ".toml"
+    }
+
+    pub fn load(group: &str, state_dir: &str)-> Result<State, AlpsError>{
+        let path = State::path(group, state_dir);
+
+        match fs::read_to_string(&path){
+            Ok(text) =>{
+                toml::from_str(&text)
+                    .map_err(|error| AlpsError::invalid("state", &(path.to_owned() + ": " + error.message())))
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(State::default()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn save(&self, group: &str, state_dir: &str)-> Result<(), AlpsError>{
+        let text = toml::to_string_pretty(self)
+            .map_err(|error| AlpsError::invalid("state", &error.to_string()))?;
+
+        fs::create_dir_all(state_dir.to_owned() + DIR)?;
+        fs::write(State::path(group, state_dir), text)?;
+        Ok(())
+    }
+}
+
+// Hex sha256 of a script's contents.
+pub(crate) fn hash(contents: &[u8])-> String{
+    Sha256::digest(contents)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
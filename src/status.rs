@@ -0,0 +1,210 @@
+use std::{
+    fs,
+    path::Path,
+    collections::HashMap,
+};
+use crate::util;
+use crate::backend::{self, PackageBackend};
+use crate::flatpak::{self, Flatpak};
+use crate::group::Group;
+use crate::manifest::{Manifest, Mode};
+use crate::diff;
+use crate::attributes;
+use crate::sync::{self, Phase};
+use crate::error::AlpsError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status{
+    // Packages, flatpaks and tools.
+    Installed,
+    Outdated,
+    Missing,
+    // Configs, Missing doubling as not on the system.
+    Identical,
+    Modified,
+    // Scripts, as recorded by the last sync on this machine.
+    NeverRun,
+    Succeeded,
+    Failed,
+    // Not meant for this host, e.g. a package for another package manager.
+    Skipped(String),
+    // Can't be told on this host, e.g. flatpak isn't installed.
+    Unknown(String),
+}
+
+impl Status{
+    // Whether the entry matches the group.
+    pub fn in_sync(&self)-> bool{
+        matches!(self, Status::Installed | Status::Identical | Status::Succeeded | Status::Skipped(_))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusEntry{
+    pub phase: Phase,
+    // The group providing the entry, which differs from the one queried for includes.
+    pub group: String,
+    // The entry as written in the group.
+    pub name: String,
+    pub status: Status,
+}
+
+// Checks the group along with everything it includes, the way a sync would resolve them.
+pub(crate) fn run(group: &Group)-> Result<Vec<StatusEntry>, AlpsError>{
+    let groups = group.resolve()?;
+    let owners = sync::config_owners(&groups)?;
+    let mut entries = Vec::new();
+
+    for group in &groups{
+        let manifest = group.manifest()?;
+        let start = entries.len();
+
+        packages(group, &manifest, &mut entries);
+        flatpaks(&manifest, &mut entries);
+        tools(&manifest, &mut entries);
+        configs(group, &manifest, &owners, &mut entries)?;
+        scripts(group, &manifest, &mut entries)?;
+
+        for entry in &mut entries[start..]{
+            entry.group = group.name().to_owned();
+        }
+    }
+    Ok(entries)
+}
+
+fn push(entries: &mut Vec<StatusEntry>, phase: Phase, name: &str, status: Status){
+    entries.push(StatusEntry{ phase, group: String::new(), name: name.to_owned(), status });
+}
+
+fn installed(backend: &dyn PackageBackend, package: &str)-> Status{
+    if !backend.query_installed(package){
+        Status::Missing
+    }
+    else if backend.outdated(package){
+        Status::Outdated
+    }
+    else{
+        Status::Installed
+    }
+}
+
+fn packages(group: &Group, manifest: &Manifest, entries: &mut Vec<StatusEntry>){
+    let backend = match group.backend(){
+        Ok(backend) => backend,
+        Err(error) =>{
+            for package in &manifest.packages{
+                push(entries, Phase::Packages, &package.entry(), Status::Unknown(error.to_string()));
+            }
+            return;
+        }
+    };
+    let aur = backend::aur_helper();
+
+    for package in &manifest.packages{
+        let name = package.name.as_str();
+
+        let status = match (package.backend.as_deref(), &package.version){
+            (Some("aur"), _) if backend.name() != "pacman" => Status::Skipped(String::from("only for aur")),
+            (Some("aur"), Some(_)) => Status::Skipped(String::from("AUR helpers can't pin versions")),
+            (Some("aur"), None) => match &aur{
+                Some(aur) => installed(aur, name),
+                None => Status::Unknown(String::from("no AUR helper found")),
+            },
+            (Some(only), _) if only != backend.name() => Status::Skipped("only for ".to_owned() + only),
+            // Another version than the pinned one counts as outdated.
+            (_, Some(version)) => match backend.pinned(name, version){
+                Some(_) if backend.query_version(name, version) => Status::Installed,
+                Some(_) if backend.query_installed(name) => Status::Outdated,
+                Some(_) => Status::Missing,
+                None => Status::Skipped(backend.name().to_owned() + " can't pin versions"),
+            },
+            _ => match backend.resolve_group(name){
+                Some(members) if members.iter().all(|member| backend.query_installed(member)) => Status::Installed,
+                Some(_) => Status::Missing,
+                None => installed(backend.as_ref(), name),
+            },
+        };
+        push(entries, Phase::Packages, &package.entry(), status);
+    }
+}
+
+fn flatpaks(manifest: &Manifest, entries: &mut Vec<StatusEntry>){
+    let available = flatpak::available();
+
+    for entry in &manifest.flatpaks{
+        let entry = entry.entry();
+
+        let status = match Flatpak::parse(&entry){
+            Some(_) if !available => Status::Unknown(String::from("command (flatpak) not found on system")),
+            Some(flatpak) if flatpak.installed() => Status::Installed,
+            Some(_) => Status::Missing,
+            None => Status::Unknown(String::from("invalid flatpak")),
+        };
+        push(entries, Phase::Flatpaks, &entry, status);
+    }
+}
+
+fn tools(manifest: &Manifest, entries: &mut Vec<StatusEntry>){
+    for tool in &manifest.tools{
+        let entry = tool.tool.to_owned() + ":" + &tool.spec();
+
+        let status = match backend::from_tool(&tool.tool){
+            Some(backend) if backend.available() => installed(backend.as_ref(), &tool.spec()),
+            _ => Status::Unknown("command (".to_owned() + &tool.tool + ") not found on system"),
+        };
+        push(entries, Phase::Tools, &entry, status);
+    }
+}
+
+fn configs(group: &Group, manifest: &Manifest, owners: &HashMap<String, String>, entries: &mut Vec<StatusEntry>)-> Result<(), AlpsError>{
+    for config in &manifest.configs{
+        let entry = config.entry();
+        let source = group.path() + "/configs/" + &config.source;
+        let mut target = config.target.to_owned();
+        util::to_userdir(&mut target)?;
+
+        let status = if let Some(owner) = owners.get(&config.target).filter(|owner| *owner != group.name()){
+            Status::Skipped("overridden by group (".to_owned() + owner + ")")
+        }
+        else if !Path::new(&source).exists(){
+            Status::Unknown(String::from("contents do not exist"))
+        }
+        else if Path::new(&target).symlink_metadata().is_err(){
+            Status::Missing
+        }
+        else if manifest.mode(config) == Mode::Symlink{
+            let links = attributes::missing_links(Path::new(&source), &config.attributes);
+
+            match fs::read_link(&target){
+                Ok(link) if link == Path::new(&source) && links.is_empty() => Status::Identical,
+                _ => Status::Modified,
+            }
+        }
+        else{
+            let diff = diff::config(group, config)?;
+
+            match diff.error{
+                None if diff.locked => Status::Unknown(String::from("secret, not compared")),
+                Some(error) => Status::Unknown(error),
+                None if diff.changed() => Status::Modified,
+                None => Status::Identical,
+            }
+        };
+        push(entries, Phase::Configs, &entry, status);
+    }
+    Ok(())
+}
+
+fn scripts(group: &Group, manifest: &Manifest, entries: &mut Vec<StatusEntry>)-> Result<(), AlpsError>{
+    let state = group.state()?;
+
+    for script in &manifest.scripts{
+        let status = match state.scripts.get(&script.name){
+            Some(run) if run.succeeded => Status::Succeeded,
+            Some(_) => Status::Failed,
+            None => Status::NeverRun,
+        };
+        push(entries, Phase::Scripts, &script.name, status);
+    }
+    Ok(())
+}
@@ -0,0 +1,935 @@
+use std::{
+    fs,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+    io::ErrorKind,
+    os::unix::process::ExitStatusExt,
+};
+use crate::util;
+use crate::backend::{self, PackageBackend};
+use crate::flatpak::{self, Flatpak};
+use crate::group::Group;
+use crate::manifest::{Mode, Config, Script, Hook, Run, OnFailure, RETRIES};
+use crate::backup::Snapshot;
+use crate::state::{self, ScriptRun};
+use crate::template::Vars;
+use crate::secret;
+use crate::attributes;
+use crate::ignore::Ignore;
+use crate::error::AlpsError;
+
+// An entry as written in the group and what gets handed to its backend.
+type Pair = (String, String);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase{
+    Packages,
+    Flatpaks,
+    Tools,
+    Configs,
+    Scripts,
+}
+
+impl Phase{
+    pub const ALL: [Phase; 5] = [
+        Phase::Packages,
+        Phase::Flatpaks,
+        Phase::Tools,
+        Phase::Configs,
+        Phase::Scripts
+    ];
+
+    pub fn name(&self)-> &'static str{
+        match self{
+            Phase::Packages => "packages",
+            Phase::Flatpaks => "flatpaks",
+            Phase::Tools => "tools",
+            Phase::Configs => "configs",
+            Phase::Scripts => "scripts",
+        }
+    }
+
+    // Hooks run right before and after the phase.
+    pub fn before(&self)-> Option<Hook>{
+        match self{
+            Phase::Packages => Some(Hook::PrePackages),
+            Phase::Configs => Some(Hook::PreConfigs),
+            _ => None,
+        }
+    }
+
+    pub fn after(&self)-> Option<Hook>{
+        match self{
+            Phase::Packages => Some(Hook::PostPackages),
+            Phase::Configs => Some(Hook::PostConfigs),
+            Phase::Scripts => Some(Hook::Final),
+            _ => None,
+        }
+    }
+}
+
+pub struct SyncOptions{
+    pub phases: Vec<Phase>,
+    // Reports what would be done as Planned without touching the system or the group.
+    pub dry_run: bool,
+}
+
+impl SyncOptions{
+    pub fn only(phase: Phase)-> SyncOptions{
+        SyncOptions{ phases: vec![phase], dry_run: false }
+    }
+
+    pub fn dry_run(mut self, dry_run: bool)-> SyncOptions{
+        self.dry_run = dry_run;
+        self
+    }
+}
+
+impl Default for SyncOptions{
+    fn default()-> SyncOptions{
+        SyncOptions{ phases: Phase::ALL.to_vec(), dry_run: false }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome{
+    // Installed, copied or run by this sync.
+    Synced,
+    // Already on the system, nothing was done.
+    Present,
+    // Left alone on this host, e.g. a package meant for another package manager.
+    Skipped(String),
+    Failed(String),
+    // What a dry run would do, e.g. "install ripgrep with pacman".
+    Planned(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct SyncEntry{
+    pub phase: Phase,
+    // The group providing the entry, which differs from the one synced for includes.
+    pub group: String,
+    // The entry as written in the group.
+    pub name: String,
+    // Scripts run as a hook, reported with the scripts phase.
+    pub hook: Option<Hook>,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, Default)]
+pub struct SyncReport{
+    pub entries: Vec<SyncEntry>,
+}
+
+impl SyncReport{
+    fn push(&mut self, phase: Phase, name: &str, outcome: Outcome){
+        self.entries.push(SyncEntry{ phase, group: String::new(), name: name.to_owned(), hook: None, outcome });
+    }
+
+    // Entries of the phase itself, without the hooks run around it.
+    pub fn phase(&self, phase: Phase)-> impl Iterator<Item = &SyncEntry>{
+        self.entries.iter().filter(move |entry| entry.phase == phase && entry.hook.is_none())
+    }
+
+    pub fn hook(&self, hook: Hook)-> impl Iterator<Item = &SyncEntry>{
+        self.entries.iter().filter(move |entry| entry.hook == Some(hook))
+    }
+
+    pub fn synced(&self)-> usize{
+        self.entries.iter().filter(|entry| entry.outcome == Outcome::Synced).count()
+    }
+
+    pub fn failed(&self)-> usize{
+        self.entries.iter().filter(|entry| matches!(entry.outcome, Outcome::Failed(_))).count()
+    }
+
+    pub fn planned(&self)-> usize{
+        self.entries.iter().filter(|entry| matches!(entry.outcome, Outcome::Planned(_))).count()
+    }
+}
+
+// Syncs the group along with everything it includes, phase by phase.
+pub(crate) fn run(group: &Group, options: &SyncOptions)-> Result<SyncReport, AlpsError>{
+    let mut report = SyncReport::default();
+    let groups = group.resolve()?;
+    let owners = config_owners(&groups)?;
+    // Shared by every group synced, so one rollback undoes the whole sync.
+    let mut snapshot = Snapshot::new(group.state_dir(), group.name());
+    // The script that failed with the abort policy, stopping every phase, script and hook
+    // after it.
+    let mut aborted = None;
+
+    for phase in Phase::ALL{
+        if !options.phases.contains(&phase){
+            continue;
+        }
+
+        if let Some(hook) = phase.before(){
+            sync_hooks(&groups, hook, options, &mut aborted, &mut report)?;
+        }
+
+        for group in &groups{
+            let start = report.entries.len();
+
+            match (phase, aborted.clone()){
+                (Phase::Scripts, _) => sync_scripts(group, None, options, &mut aborted, &mut report)?,
+                (_, Some(failed)) => skip_phase(group, phase, &failed, &mut report)?,
+                (Phase::Packages, None) => sync_packages(group, options, &mut report)?,
+                (Phase::Flatpaks, None) => sync_flatpaks(group, options, &mut report)?,
+                (Phase::Tools, None) => sync_tools(group, options, &mut report)?,
+                (Phase::Configs, None) => sync_configs(group, options, &owners, &mut snapshot, &mut report)?,
+            }
+
+            for entry in &mut report.entries[start..]{
+                entry.group = group.name().to_owned();
+            }
+        }
+
+        if let Some(hook) = phase.after(){
+            sync_hooks(&groups, hook, options, &mut aborted, &mut report)?;
+        }
+    }
+    Ok(report)
+}
+
+// Runs the scripts of every group hooked to this point, reported with the scripts phase.
+fn sync_hooks(groups: &[Group], hook: Hook, options: &SyncOptions, aborted: &mut Option<String>, report: &mut SyncReport)-> Result<(), AlpsError>{
+    for group in groups{
+        let start = report.entries.len();
+        sync_scripts(group, Some(hook), options, aborted, report)?;
+
+        for entry in &mut report.entries[start..]{
+            entry.group = group.name().to_owned();
+            entry.hook = Some(hook);
+        }
+    }
+    Ok(())
+}
+
+// Reports every entry of a phase as skipped, the sync having been aborted before it.
+fn skip_phase(group: &Group, phase: Phase, failed: &str, report: &mut SyncReport)-> Result<(), AlpsError>{
+    let manifest = group.manifest()?;
+
+    let entries = match phase{
+        Phase::Packages => manifest.packages.iter().map(|package| package.entry()).collect(),
+        Phase::Flatpaks => manifest.flatpaks.iter().map(|flatpak| flatpak.entry()).collect(),
+        Phase::Tools => manifest.tools.iter().map(|tool| tool.tool.to_owned() + ":" + &tool.spec()).collect(),
+        Phase::Configs => manifest.configs.iter().map(Config::entry).collect(),
+        Phase::Scripts => Vec::new(),
+    };
+
+    for entry in entries{
+        report.push(phase, &entry, after_abort(failed));
+    }
+    Ok(())
+}
+
+fn after_abort(failed: &str)-> Outcome{
+    Outcome::Skipped("aborted after (".to_owned() + failed + ") failed")
+}
+
+// The group each config target is synced from. Groups later in the resolved order win,
+// so a group overrides the configs of those it includes.
+pub(crate) fn config_owners(groups: &[Group])-> Result<HashMap<String, String>, AlpsError>{
+    let mut owners = HashMap::new();
+
+    for group in groups{
+        for config in group.manifest()?.configs{
+            owners.insert(config.target, group.name().to_owned());
+        }
+    }
+    Ok(owners)
+}
+
+// Installs a batch, marking every entry of it synced or failed.
+fn install_batch(backend: &dyn PackageBackend, batch: &[Pair], phase: Phase, options: &SyncOptions, report: &mut SyncReport){
+    if batch.is_empty(){
+        return;
+    }
+
+    if options.dry_run{
+        for (entry, package) in batch{
+            report.push(phase, entry, Outcome::Planned("install ".to_owned() + package + " with " + backend.name()));
+        }
+        return;
+    }
+
+    let packages = batch.iter()
+        .map(|(_, package)| package.to_owned())
+        .collect::<Vec<String>>();
+
+    let outcome = match backend.install(&packages){
+        Ok(()) => Outcome::Synced,
+        Err(error) => Outcome::Failed(error.to_string()),
+    };
+
+    for (entry, _) in batch{
+        report.push(phase, entry, outcome.clone());
+    }
+}
+
+fn sync_packages(group: &Group, options: &SyncOptions, report: &mut SyncReport)-> Result<(), AlpsError>{
+    let entries = group.manifest()?.packages;
+    if entries.is_empty(){
+        return Ok(());
+    }
+
+    let backend = match group.backend(){
+        Ok(backend) => backend,
+        Err(error) =>{
+            for package in &entries{
+                report.push(Phase::Packages, &package.entry(), Outcome::Failed(error.to_string()));
+            }
+            return Ok(());
+        }
+    };
+    let aur = backend::aur_helper();
+
+    // (entry, package) pairs, the entry keeping its aur: prefix.
+    let mut packages = Vec::new();
+    let mut aur_packages = Vec::new();
+
+    for package in &entries{
+        let entry = package.entry();
+        let name = package.name.as_str();
+
+        // The AUR only serves Arch, so hosts sharing the group with other package managers
+        // leave it alone like any package meant for another one.
+        if package.backend.as_deref() == Some("aur") && backend.name() != "pacman"{
+            report.push(Phase::Packages, &entry, Outcome::Skipped(String::from("only for aur")));
+        }
+        else if package.backend.as_deref() == Some("aur") && package.version.is_some(){
+            report.push(Phase::Packages, &entry, Outcome::Skipped(String::from("AUR helpers can't pin versions")));
+        }
+        else if package.backend.as_deref() == Some("aur"){
+            match &aur{
+                Some(aur) if aur.query_installed(name) => report.push(Phase::Packages, &entry, Outcome::Present),
+                Some(_) => aur_packages.push((entry, name.to_owned())),
+                None =>{
+                    report.push(
+                        Phase::Packages,
+                        &entry,
+                        Outcome::Failed(
+                            "no AUR helper found (install one of ".to_owned()
+                                + &backend::AUR_HELPERS.join(", ")
+                                + " or set ALPS_AUR_HELPER)"
+                        )
+                    );
+                }
+            }
+        }
+        else if let Some(only) = package.backend.as_deref().filter(|only| *only != backend.name()){
+            report.push(Phase::Packages, &entry, Outcome::Skipped("only for ".to_owned() + only));
+        }
+        else if let Some(version) = &package.version{
+            match backend.pinned(name, version){
+                Some(_) if backend.query_version(name, version) => report.push(Phase::Packages, &entry, Outcome::Present),
+                Some(pinned) => packages.push((entry, pinned)),
+                None => report.push(Phase::Packages, &entry, Outcome::Skipped(backend.name().to_owned() + " can't pin versions")),
+            }
+        }
+        else if backend.query_installed(name){
+            report.push(Phase::Packages, &entry, Outcome::Present);
+        }
+        else if let Some(members) = backend.resolve_group(name){
+            if members.iter().any(|member| !backend.query_installed(member)){
+                packages.push((entry, name.to_owned()));
+            }
+            else{
+                report.push(Phase::Packages, &entry, Outcome::Present);
+            }
+        }
+        else if backend.exists(name){
+            packages.push((entry, name.to_owned()));
+        }
+        else if aur.as_ref().is_some_and(|aur| aur.exists(name)){
+            aur_packages.push((entry, name.to_owned()));
+        }
+        else{
+            report.push(Phase::Packages, &entry, Outcome::Failed(String::from("does not exist in repository")));
+        }
+    }
+
+    install_batch(backend.as_ref(), &packages, Phase::Packages, options, report);
+    if let Some(aur) = &aur{
+        install_batch(aur, &aur_packages, Phase::Packages, options, report);
+    }
+    Ok(())
+}
+
+fn sync_flatpaks(group: &Group, options: &SyncOptions, report: &mut SyncReport)-> Result<(), AlpsError>{
+    let entries = group.flatpaks()?;
+    if entries.is_empty(){
+        return Ok(());
+    }
+
+    if !flatpak::available(){
+        for entry in &entries{
+            report.push(Phase::Flatpaks, entry, Outcome::Failed(String::from("command (flatpak) not found on system")));
+        }
+        return Ok(());
+    }
+
+    let remotes = group.flatpak_remotes()?;
+    // Apps batched per (user, remote), each kept as (entry, app).
+    let mut batches: Vec<(bool, &str, Vec<Pair>)> = Vec::new();
+    // Remotes a dry run would add, reported with the first app needing them.
+    let mut planned: Vec<(bool, &str)> = Vec::new();
+
+    for entry in &entries{
+        let flatpak = match Flatpak::parse(entry){
+            Some(flatpak) => flatpak,
+            None =>{
+                report.push(Phase::Flatpaks, entry, Outcome::Failed(String::from("invalid flatpak")));
+                continue;
+            }
+        };
+
+        if flatpak.installed(){
+            report.push(Phase::Flatpaks, entry, Outcome::Present);
+            continue;
+        }
+
+        if !flatpak::has_remote(flatpak.user, flatpak.remote){
+            let added = match flatpak::find_remote(flatpak.remote, &remotes){
+                Some(url) if options.dry_run =>{
+                    if !planned.contains(&(flatpak.user, flatpak.remote)){
+                        report.push(Phase::Flatpaks, entry, Outcome::Planned("add remote ".to_owned() + flatpak.remote + " from " + &url));
+                        planned.push((flatpak.user, flatpak.remote));
+                    }
+                    true
+                }
+                Some(url) => flatpak::add_remote(flatpak.user, flatpak.remote, &url).is_ok(),
+                None => false,
+            };
+
+            if !added{
+                report.push(
+                    Phase::Flatpaks,
+                    entry,
+                    Outcome::Failed("remote (".to_owned() + flatpak.remote + ") could not be added")
+                );
+                continue;
+            }
+        }
+
+        let app = (entry.to_owned(), flatpak.app.to_owned());
+        match batches.iter_mut().find(|(user, remote, _)| *user == flatpak.user && *remote == flatpak.remote){
+            Some((_, _, apps)) => apps.push(app),
+            None => batches.push((flatpak.user, flatpak.remote, vec![app])),
+        }
+    }
+
+    for (user, remote, apps) in &batches{
+        if options.dry_run{
+            for (entry, app) in apps{
+                report.push(Phase::Flatpaks, entry, Outcome::Planned("install ".to_owned() + app + " from " + remote));
+            }
+            continue;
+        }
+
+        let names = apps.iter()
+            .map(|(_, app)| app.to_owned())
+            .collect::<Vec<String>>();
+
+        let outcome = match flatpak::install(*user, remote, &names){
+            Ok(()) => Outcome::Synced,
+            Err(error) => Outcome::Failed(error.to_string()),
+        };
+
+        for (entry, _) in apps{
+            report.push(Phase::Flatpaks, entry, outcome.clone());
+        }
+    }
+    Ok(())
+}
+
+fn sync_tools(group: &Group, options: &SyncOptions, report: &mut SyncReport)-> Result<(), AlpsError>{
+    let tools = group.tools()?;
+
+    for (tool, _) in backend::TOOLS{
+        let specs = tools.iter()
+            .filter(|(kind, _)| kind == tool)
+            .map(|(kind, spec)| (kind.to_owned() + ":" + spec, spec.to_owned()))
+            .collect::<Vec<Pair>>();
+
+        if specs.is_empty(){
+            continue;
+        }
+
+        let backend = match backend::from_tool(tool){
+            Some(backend) if backend.available() => backend,
+            _ =>{
+                for (entry, _) in &specs{
+                    report.push(
+                        Phase::Tools,
+                        entry,
+                        Outcome::Failed("command (".to_owned() + tool + ") not found on system")
+                    );
+                }
+                continue;
+            }
+        };
+
+        let mut missing = Vec::new();
+        for (entry, spec) in specs{
+            if backend.query_installed(&spec){
+                report.push(Phase::Tools, &entry, Outcome::Present);
+            }
+            else{
+                missing.push((entry, spec));
+            }
+        }
+
+        install_batch(backend.as_ref(), &missing, Phase::Tools, options, report);
+    }
+    Ok(())
+}
+
+fn sync_configs(group: &Group, options: &SyncOptions, owners: &HashMap<String, String>, snapshot: &mut Snapshot, report: &mut SyncReport)-> Result<(), AlpsError>{
+    let manifest = group.manifest()?;
+
+    let vars = if manifest.configs.iter().any(|config| config.template){
+        Some(Vars::load(group.state_dir())?)
+    }
+    else{
+        None
+    };
+
+    for config in &manifest.configs{
+        let entry = config.entry();
+        let mut path_dst = config.target.to_owned();
+        util::to_userdir(&mut path_dst)?;
+
+        let path_src = group.path() + "/configs/" + &config.source;
+
+        if let Some(owner) = owners.get(&config.target).filter(|owner| *owner != group.name()){
+            report.push(Phase::Configs, &entry, Outcome::Skipped("overridden by group (".to_owned() + owner + ")"));
+            continue;
+        }
+
+        if !Path::new(&path_src).exists(){
+            report.push(Phase::Configs, &entry, Outcome::Failed(String::from("contents do not exist")));
+
+            if !options.dry_run{
+                util::config_write(group.name(), "[CONFIGS]", &entry, group.home_dir(), false)?;
+            }
+            continue;
+        }
+
+        let ignore = match Ignore::new(&manifest, Some(config)){
+            Ok(ignore) => ignore,
+            Err(error) =>{
+                report.push(Phase::Configs, &entry, Outcome::Failed(error.to_string()));
+                continue;
+            }
+        };
+
+        let result = match manifest.mode(config){
+            Mode::Copy => copy_config(snapshot, config, &ignore, &path_src, &path_dst, vars.as_ref(), options),
+            Mode::Symlink if config.template => Ok(vec![Outcome::Failed(String::from("templated configs can't be symlinked"))]),
+            Mode::Symlink if config.secret => Ok(vec![Outcome::Failed(String::from("secret configs can't be symlinked"))]),
+            Mode::Symlink => link_config(snapshot, config, &path_src, &path_dst, options),
+        };
+
+        match result{
+            Ok(outcomes) =>{
+                for outcome in outcomes{
+                    report.push(Phase::Configs, &entry, outcome);
+                }
+            }
+            Err(error) => report.push(Phase::Configs, &entry, Outcome::Failed(error.to_string())),
+        }
+    }
+    Ok(())
+}
+
+// Templated and secret configs are rendered or decrypted file by file instead of copied.
+// Files are then given the modes, owners and links recorded with the config.
+fn copy_config(snapshot: &mut Snapshot, config: &Config, ignore: &Ignore, path_src: &str, path_dst: &str, vars: Option<&Vars>, options: &SyncOptions)-> Result<Vec<Outcome>, AlpsError>{
+    let target = Path::new(path_dst);
+    let template = vars.filter(|_| config.template);
+    let secret = config.secret;
+    let files = ignore.targets(path_src, path_dst)?;
+
+    if options.dry_run{
+        let mut outcomes = Vec::new();
+
+        if target.is_symlink(){
+            outcomes.push(Outcome::Planned("replace the link at ".to_owned() + path_dst + " with a copy"));
+        }
+        // A dry run doesn't ask for the passphrase just to compare.
+        else if secret && !secret::unlocked(){
+            outcomes.push(Outcome::Planned("sync ".to_owned() + path_dst + ", secret, not compared"));
+        }
+        else{
+            for (source, file) in &files{
+                // Files already up to date are left alone, as back_up does.
+                let live = fs::read(file).ok();
+                if live.is_some() && live == Some(contents(source, template, secret)?){
+                    continue;
+                }
+
+                let action = if file.exists(){ "overwrite " } else{ "create " };
+                outcomes.push(Outcome::Planned(action.to_owned() + &file.display().to_string()));
+            }
+        }
+        for (path, link) in attributes::missing_links(target, &config.attributes){
+            outcomes.push(Outcome::Planned("link ".to_owned() + &path + " to " + &link));
+        }
+
+        if outcomes.is_empty(){
+            outcomes.push(Outcome::Present);
+        }
+        return Ok(outcomes);
+    }
+
+    // Read up front so an undefined variable or a wrong passphrase leaves every file alone.
+    let mut rendered = Vec::new();
+    if template.is_some() || secret{
+        for (source, target) in &files{
+            rendered.push((source, target, contents(source, template, secret)?));
+        }
+    }
+
+    // A symlink left by the symlink mode would have the copy write into the group itself.
+    if target.is_symlink(){
+        if let Err(error) = snapshot.replace(target){
+            return Ok(vec![backup_failed(error)]);
+        }
+    }
+
+    if template.is_none() && !secret{
+        if let Err(error) = back_up(snapshot, &files){
+            return Ok(vec![backup_failed(error)]);
+        }
+
+        for (source, target) in &files{
+            util::copy_dir(source, target)?;
+        }
+    }
+
+    for (source, target, contents) in &rendered{
+        if !fs::read(target).is_ok_and(|live| live == *contents){
+            if let Err(error) = snapshot.save(target){
+                return Ok(vec![backup_failed(error)]);
+            }
+            util::write_file(target, contents)?;
+        }
+
+        // Secrets stay at the 0600 of their decrypted copy, unless a mode was recorded.
+        if !secret{
+            attributes::copy_mode(source, target)?;
+        }
+    }
+
+    attributes::apply(target, &config.attributes, snapshot)?;
+    Ok(vec![Outcome::Synced])
+}
+
+// What syncing a stored file writes, decrypted and then rendered.
+pub(crate) fn contents(source: &Path, template: Option<&Vars>, secret: bool)-> Result<Vec<u8>, AlpsError>{
+    let contents = if secret{
+        secret::read(source)?
+    }
+    else{
+        fs::read(source)?
+    };
+
+    let vars = match template{
+        Some(vars) => vars,
+        None => return Ok(contents),
+    };
+
+    // Files that aren't text, e.g. images in a templated directory, are written as they are.
+    match String::from_utf8(contents){
+        Ok(text) => Ok(vars.render(&text)?.into_bytes()),
+        Err(error) => Ok(error.into_bytes()),
+    }
+}
+
+fn backup_failed(error: AlpsError)-> Outcome{
+    Outcome::Failed("backup failed: ".to_owned() + &error.to_string())
+}
+
+fn link_config(snapshot: &mut Snapshot, config: &Config, path_src: &str, path_dst: &str, options: &SyncOptions)-> Result<Vec<Outcome>, AlpsError>{
+    let target = Path::new(path_dst);
+    let linked = fs::read_link(target).is_ok_and(|link| link == Path::new(path_src));
+    // The links inside the config live in the stored copy, which the target shows.
+    let links = attributes::missing_links(Path::new(path_src), &config.attributes);
+
+    if linked && links.is_empty(){
+        return Ok(vec![Outcome::Present]);
+    }
+
+    let exists = target.symlink_metadata().is_ok();
+
+    if options.dry_run{
+        let mut outcomes = Vec::new();
+
+        if !linked{
+            let action = if exists{ "replace " } else{ "create " };
+            outcomes.push(Outcome::Planned(action.to_owned() + path_dst + " as a link to " + path_src));
+        }
+        for (path, link) in links{
+            outcomes.push(Outcome::Planned("link ".to_owned() + &path + " to " + &link));
+        }
+        return Ok(outcomes);
+    }
+
+    attributes::link_stored(Path::new(path_src), &config.attributes)?;
+
+    if linked{
+        return Ok(vec![Outcome::Synced]);
+    }
+
+    let backup = if exists{
+        snapshot.replace(target)
+    }
+    else{
+        snapshot.save(target)
+    };
+
+    if let Err(error) = backup{
+        return Ok(vec![backup_failed(error)]);
+    }
+
+    if let Some(parent) = target.parent(){
+        fs::create_dir_all(parent)?;
+    }
+    std::os::unix::fs::symlink(path_src, target)?;
+
+    Ok(vec![Outcome::Synced])
+}
+
+// Saves every file the copy would overwrite or create, skipping ones already up to date.
+fn back_up(snapshot: &mut Snapshot, files: &[(PathBuf, PathBuf)])-> Result<(), AlpsError>{
+    for (source, target) in files{
+        let unchanged = matches!(
+            (fs::read(source), fs::read(target)),
+            (Ok(source), Ok(target)) if source == target
+        );
+
+        if !unchanged{
+            snapshot.save(target)?;
+        }
+    }
+    Ok(())
+}
+
+// The inverse of syncing a config, copying its live contents back over the stored copy.
+pub(crate) fn capture_config(group: &Group, config: &Config)-> Result<Outcome, AlpsError>{
+    let mut path_live = config.target.to_owned();
+    util::to_userdir(&mut path_live)?;
+
+    if config.template{
+        return Ok(Outcome::Failed(String::from("templated configs can't be updated from their rendered copy")));
+    }
+
+    let path_stored = group.path() + "/configs/" + &config.source;
+    let live = Path::new(&path_live);
+    let stored = Path::new(&path_stored);
+
+    if fs::read_link(live).is_ok_and(|link| link == stored){
+        return Ok(Outcome::Present);
+    }
+    else if !live.exists(){
+        return Ok(Outcome::Failed("live path (".to_owned() + &path_live + ") does not exist"));
+    }
+
+    if config.secret{
+        secret::unlock()?;
+    }
+
+    // The stored files as syncing would write them. Secrets are decrypted up front, so
+    // one that doesn't decrypt under this passphrase fails instead of counting as changed.
+    let mut current = HashMap::new();
+    for (file, _) in util::copy_targets(stored, stored)?{
+        let contents = contents(&file, None, config.secret)?;
+        current.insert(file, contents);
+    }
+
+    // Same files with the same contents on both sides. Ignored files left in the group
+    // count as a change, so they get cleared out.
+    let ignore = Ignore::new(&group.manifest()?, Some(config))?;
+    let files = ignore.targets(live, stored)?;
+    let unchanged = files.len() == current.len()
+        && files.iter().all(|(live, stored)|{
+            current.get(stored).is_some_and(|stored| fs::read(live).is_ok_and(|live| live == *stored))
+        });
+
+    let recorded = attributes::record(live, &ignore)?;
+
+    if unchanged && recorded == config.attributes{
+        return Ok(Outcome::Present);
+    }
+
+    if !unchanged{
+        // Stored next to the old copy, which is only replaced once every file made it. Files
+        // deleted on the system don't linger in the group and a failure leaves it as it was.
+        let path_new = group.path() + "/configs/." + &config.source + ".new";
+        let new = Path::new(&path_new);
+        remove_path(new)?;
+
+        if live.is_dir(){
+            fs::create_dir_all(new)?;
+        }
+
+        if let Err(error) = ignore.targets(live, new).and_then(|files| store_files(&files, config.secret)){
+            let _ = remove_path(new);
+            return Err(error);
+        }
+
+        remove_path(stored)?;
+        fs::rename(new, stored)?;
+    }
+
+    if recorded != config.attributes{
+        let mut manifest = group.manifest()?;
+        if let Some(entry) = manifest.configs.iter_mut().find(|entry| entry.source == config.source){
+            entry.attributes = recorded;
+        }
+        group.save(&manifest)?;
+    }
+
+    Ok(Outcome::Synced)
+}
+
+fn remove_path(path: &Path)-> Result<(), AlpsError>{
+    if path.is_dir(){
+        fs::remove_dir_all(path)?;
+    }
+    else if path.exists(){
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+// Copies (live, stored) pairs into the group, encrypting them for secret configs.
+pub(crate) fn store_files(files: &[(PathBuf, PathBuf)], secret: bool)-> Result<(), AlpsError>{
+    for (live, stored) in files{
+        if secret{
+            secret::copy_file(live, stored)?;
+        }
+        else{
+            util::copy_dir(live, stored)?;
+        }
+    }
+    Ok(())
+}
+
+// Runs the scripts of one hook, or those without a hook for the scripts phase.
+fn sync_scripts(group: &Group, hook: Option<Hook>, options: &SyncOptions, aborted: &mut Option<String>, report: &mut SyncReport)-> Result<(), AlpsError>{
+    let manifest = group.manifest()?;
+    let mut state = group.state()?;
+
+    for script in manifest.script_order()?.into_iter().filter(|script| script.hook == hook){
+        let name = script.name.as_str();
+        let script_path = group.path() + "/scripts/" + name;
+
+        if let Some(failed) = aborted{
+            report.push(Phase::Scripts, name, after_abort(failed));
+            continue;
+        }
+
+        let hash = fs::read(&script_path).ok().map(|contents| state::hash(&contents));
+        let succeeded = state.scripts.get(name).filter(|last| last.succeeded);
+
+        let skip = match script.run.unwrap_or_default(){
+            Run::Always => None,
+            Run::Once => succeeded.map(|_| "already ran once"),
+            Run::OnChange => succeeded.filter(|last| hash.is_some() && last.hash == hash).map(|_| "unchanged since its last run"),
+        };
+
+        if let Some(reason) = skip{
+            report.push(Phase::Scripts, name, Outcome::Skipped(reason.to_owned()));
+            continue;
+        }
+
+        if options.dry_run{
+            let command = script.interpreter.iter()
+                .chain([&script_path])
+                .chain(&script.args)
+                .map(String::as_str)
+                .collect::<Vec<&str>>()
+                .join(" ");
+            report.push(Phase::Scripts, name, Outcome::Planned("run ".to_owned() + &command));
+            continue;
+        }
+
+        let policy = manifest.on_failure(script);
+        let retries = match policy{
+            OnFailure::Retry => script.retries.unwrap_or(RETRIES),
+            _ => 0,
+        };
+
+        let mut outcome = run_script(script, &script_path);
+        let mut tries = 1;
+
+        while matches!(outcome, Outcome::Failed(_)) && tries <= retries{
+            outcome = run_script(script, &script_path);
+            tries += 1;
+        }
+
+        if let Outcome::Failed(reason) = &mut outcome{
+            if tries > 1{
+                *reason += &(" after (".to_owned() + &tries.to_string() + ") tries");
+            }
+            if policy == OnFailure::Abort{
+                *aborted = Some(name.to_owned());
+            }
+        }
+
+        state.scripts.insert(
+            name.to_owned(),
+            ScriptRun{ succeeded: outcome == Outcome::Synced, ran: util::timestamp(), hash }
+        );
+        group.save_state(&state)?;
+
+        report.push(Phase::Scripts, name, outcome);
+    }
+    Ok(())
+}
+
+// Runs a script once. It failing is left to the policy, the script always stays in the group.
+fn run_script(script: &Script, script_path: &str)-> Outcome{
+    let mut handle = match &script.interpreter{
+        Some(interpreter) =>{
+            let mut handle = Command::new(interpreter);
+            handle.arg(script_path);
+            handle
+        }
+        None => Command::new("/".to_owned() + script_path),
+    };
+    handle.args(&script.args).envs(&script.env);
+
+    if let Some(dir) = &script.dir{
+        let mut dir = dir.to_owned();
+        if let Err(error) = util::to_userdir(&mut dir){
+            return Outcome::Failed(error.to_string());
+        }
+
+        // Checked here, as spawning would report it like a missing script.
+        if !Path::new(&dir).is_dir(){
+            return Outcome::Failed("working directory (".to_owned() + &dir + ") does not exist");
+        }
+        handle.current_dir(dir);
+    }
+
+    match handle.status(){
+        Ok(status) if status.success() => Outcome::Synced,
+        Ok(status) => match status.code(){
+            Some(code) => Outcome::Failed("exited with code ".to_owned() + &code.to_string()),
+            None => Outcome::Failed("killed by signal ".to_owned() + &status.signal().unwrap_or_default().to_string()),
+        },
+        Err(error) => match &script.interpreter{
+            Some(interpreter) => Outcome::Failed("interpreter (".to_owned() + interpreter + ") failed to start: " + &error.to_string()),
+            None if error.kind() == ErrorKind::NotFound => Outcome::Failed(String::from("contents do not exist")),
+            None if error.kind() == ErrorKind::PermissionDenied =>{
+                Outcome::Failed(String::from("not executable, set an interpreter or its exec bit"))
+            }
+            None => Outcome::Failed("failed to run: ".to_owned() + &error.to_string()),
+        },
+    }
+}
@@ -0,0 +1,169 @@
+use std::{
+    fs,
+    env,
+    cell::OnceCell,
+    collections::BTreeMap,
+    io::ErrorKind,
+    process::Command,
+};
+use crate::util;
+use crate::error::AlpsError;
+
+// Variables of this machine, kept out of the shared store at <state_dir>/vars.toml.
+pub const FILE: &str = "vars.toml";
+// Where older versions kept them, inside the store.
+pub const LEGACY_FILE: &str = ".vars.toml";
+
+// Templated configs have every {{ name }} replaced by the variable of that name. The vars
+// file comes first, then the built in hostname, username, home and dpi.
+pub struct Vars{
+    vars: BTreeMap<String, String>,
+    dpi: OnceCell<Option<String>>,
+}
+
+impl Vars{
+    pub fn path(state_dir: &str)-> String{
+        state_dir.to_owned() + FILE
+    }
+
+    pub fn load(state_dir: &str)-> Result<Vars, AlpsError>{
+        let path = Vars::path(state_dir);
+
+        let table: toml::Table = match fs::read_to_string(&path){
+            Ok(text) =>{
+                toml::from_str(&text)
+                    .map_err(|error| AlpsError::invalid("vars", &(path.to_owned() + ": " + error.message())))?
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => toml::Table::new(),
+            Err(error) => return Err(error.into()),
+        };
+
+        let vars = table.into_iter()
+            .map(|(name, value)|{
+                let value = match value{
+                    toml::Value::String(value) => value,
+                    value => value.to_string(),
+                };
+                (name, value)
+            })
+            .collect();
+
+        Ok(Vars{ vars, dpi: OnceCell::new() })
+    }
+
+    pub fn get(&self, name: &str)-> Option<String>{
+        if let Some(value) = self.vars.get(name){
+            return Some(value.to_owned());
+        }
+
+        match name{
+            "hostname" => hostname(),
+            "username" => username(),
+            "home" => util::user_home().ok(),
+            "dpi" => self.dpi.get_or_init(dpi).to_owned(),
+            _ => None,
+        }
+    }
+
+    pub fn render(&self, text: &str)-> Result<String, AlpsError>{
+        let mut rendered = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find("{{"){
+            let (before, tag) = rest.split_at(start);
+            let end = match tag.find("}}"){
+                Some(end) => end,
+                None => break,
+            };
+
+            let name = tag[2..end].trim();
+            let value = self.get(name).ok_or_else(|| AlpsError::invalid("template variable", name))?;
+
+            rendered.push_str(before);
+            rendered.push_str(&value);
+            rest = &tag[end + 2..];
+        }
+        rendered.push_str(rest);
+
+        Ok(rendered)
+    }
+}
+
+fn hostname()-> Option<String>{
+    ["/proc/sys/kernel/hostname", "/etc/hostname"].iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .map(|hostname| hostname.trim().to_owned())
+        .filter(|hostname| !hostname.is_empty())
+}
+
+fn username()-> Option<String>{
+    if let Ok(user) = env::var("USER").or_else(|_| env::var("LOGNAME")){
+        return Some(user);
+    }
+
+    let handle = Command::new("id")
+        .arg("-un")
+        .output()
+        .ok()?;
+
+    Some(String::from_utf8_lossy(&handle.stdout).trim().to_owned())
+        .filter(|user| handle.status.success() && !user.is_empty())
+}
+
+// Xft.dpi of the running X server or XWayland, if there is one.
+fn dpi()-> Option<String>{
+    let handle = Command::new("xrdb")
+        .arg("-query")
+        .output()
+        .ok()?;
+
+    String::from_utf8_lossy(&handle.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("Xft.dpi:"))
+        .map(|dpi| dpi.trim().to_owned())
+}
+
+#[cfg(test)]
+mod tests{
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)])-> Vars{
+        let vars = pairs.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        Vars{ vars, dpi: OnceCell::new() }
+    }
+
+    #[test]
+    fn render_variables(){
+        let vars = vars(&[("monitor", "DP-1")]);
+
+        assert_eq!(vars.render("output {{ monitor }} on").unwrap(), "output DP-1 on");
+        assert_eq!(vars.render("{{monitor}}{{  monitor  }}").unwrap(), "DP-1DP-1");
+        assert_eq!(vars.render("no tags").unwrap(), "no tags");
+    }
+
+    #[test]
+    fn render_unterminated(){
+        let vars = vars(&[("monitor", "DP-1")]);
+
+        assert_eq!(vars.render("a {{ monitor").unwrap(), "a {{ monitor");
+        assert_eq!(vars.render("{{ monitor }} {{ ").unwrap(), "DP-1 {{ ");
+        assert_eq!(vars.render("}} {{ monitor }}").unwrap(), "}} DP-1");
+    }
+
+    #[test]
+    fn render_undefined(){
+        let vars = vars(&[]);
+
+        assert!(vars.render("{{ not_a_variable }}").is_err());
+    }
+
+    #[test]
+    fn vars_override_builtins(){
+        let vars = vars(&[("hostname", "box")]);
+
+        assert_eq!(vars.get("hostname").as_deref(), Some("box"));
+    }
+}
@@ -1,29 +1,27 @@
-use colored::Colorize;
 use std::{
-    fs,
-    path::Path, 
-    io::prelude::*,
-    process::Command,
-    io::ErrorKind,
+    fs::{self, OpenOptions},
+    env,
+    path::{Path, PathBuf},
+    process::{self, Command},
+    io::{ErrorKind, Write},
+    os::unix::fs::OpenOptionsExt,
+    time::{SystemTime, UNIX_EPOCH},
 }; 
+use crate::error::AlpsError;
+use crate::manifest::Manifest;
 
-pub fn get_entries(text: &String)-> impl Iterator<Item = &str>{ 
+pub fn get_entries(text: &str)-> impl Iterator<Item = &str>{ 
     text.split(['\r', '\n'])
         .filter(|x|{
-            if x.is_empty(){
-                false
-            }
-            else{
-                true
-            }
+            !x.is_empty()
         })
 }
 
 
-pub fn dup_count(config: &str, group: &str, home_dir: &str)-> usize{
+pub fn dup_count(config: &str, group: &str, home_dir: &str)-> Result<usize, AlpsError>{
     let mut highest = 0;
 
-    for entry in get_entries(&read_label("[CONFIGS]", group, home_dir))
+    for entry in get_entries(&read_label("[CONFIGS]", group, home_dir)?)
     {
         let (name, index) = entry.rsplit_once('/').unwrap_or((entry, entry)).1
             .rsplit_once('_').unwrap_or((entry, entry));
@@ -36,277 +34,230 @@ pub fn dup_count(config: &str, group: &str, home_dir: &str)-> usize{
         }
 
     }
-    highest
+    Ok(highest)
 }
 
-pub fn to_userdir(config: &mut String){
-    let home = dirs::home_dir()
-        .unwrap()
-        .into_os_string()
-        .into_string()
-        .unwrap();
+pub fn user_home()-> Result<String, AlpsError>{
+    dirs::home_dir()
+        .and_then(|home| home.into_os_string().into_string().ok())
+        .ok_or_else(|| AlpsError::Io(ErrorKind::NotFound.into()))
+}
+
+// $XDG_STATE_HOME, or ~/.local/state when unset.
+pub fn state_home()-> Result<String, AlpsError>{
+    match env::var("XDG_STATE_HOME"){
+        Ok(dir) if dir.starts_with('/') => Ok(dir),
+        _ => Ok(user_home()? + "/.local/state"),
+    }
+}
+
+pub fn to_userdir(config: &mut String)-> Result<(), AlpsError>{
+    let home = user_home()?;
 
     if let Some(segments) = config.split_once("home_dir")
     {
-        *config = home + &segments.1; 
+        *config = home + segments.1; 
     }
+    Ok(())
 }
 
 pub fn to_template(config: &mut String){
-    if let Some(segments) = config.split_once(&dirs::home_dir()
-        .unwrap()
-        .into_os_string()
-        .into_string()
-        .unwrap())
-    {
-        *config = "home_dir".to_owned() + &segments.1;
+    if let Ok(home) = user_home(){
+        if let Some(segments) = config.split_once(&home)
+        {
+            *config = "home_dir".to_owned() + segments.1;
+        }
     }
 }
 
-pub fn help_menu(){
-    println!("{} alps <operation> [...]", "usage:".white());
-    println!("{}", "operations:".white());
-    println!("   alps {{-Q --query}} [flags] : query installed groups and their contents in your config");
-    println!("   alps {{-S --sync}} [flags] : sync your system with a group and their contents");       
-    println!("   alps {{-I --install}} [flags] : install a group and their contents in your config");
-    println!("   alps {{-R --remove}} [flags] : remove a group and their contents in your config");
-    println!("   alps {{-E --edit}} [flags] : edit a group and their contents in your config");
-    println!("{} use {{-h --help}} on any operation for list of flags", "hint:".white())
+pub fn run(handle: &mut Command, command: &str)-> Result<(), AlpsError>{
+    match handle.status(){
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(AlpsError::backend(command, status)),
+        Err(error) => Err(AlpsError::backend(command, error)),
+    }
 }
 
-pub fn edit_file(file_path: &str, editor: &str){
-    match Command::new(&editor)
-        .arg(file_path) 
-        .status()
-    {
-            Ok(_) =>{
-                let file_name = file_path.split("/configs/")
-                    .last()
-                    .unwrap();
-
-                println!(
-                    "{} Editing file ({})...",
-                    "[%]".cyan(),
-                    file_name.cyan()
-                );
-            }
-            Err(_) =>{
-                eprintln!(
-                    "{} Invalid editor! Update EDITOR environment variable...",
-                    "[!]".yellow()
-                );
-                std::process::exit(1);
-            }
+pub fn in_path(binary: &str)-> bool{
+    match env::var_os("PATH"){
+        Some(paths) => env::split_paths(&paths).any(|dir| dir.join(binary).is_file()),
+        None => false,
     }
 }
 
-pub fn find(args: Vec<String>, label: &str, home_dir: &str, group: &String, mutate: impl Fn(&str)-> &str){
-    let text = read_label(label, group, home_dir);
-
-    if !args.is_empty(){
-        let mut status = 0;
+// Falls back to sudo when the destination isn't writable, e.g. configs living under /etc.
+pub(crate) fn sudo(handle: &mut Command, path: &Path)-> Result<(), AlpsError>{
+    match handle.status(){
+        Ok(status) if status.success() => Ok(()),
+        Ok(_) => Err(AlpsError::PermissionDenied(path.display().to_string())),
+        Err(error) => Err(AlpsError::backend("sudo", error)),
+    }
+}
 
-        for arg in &args{
-            let mut found = vec![];
+// Symlinks inside a directory are left out, see attributes::record.
+pub fn copy_dir<S, D>(src: S, dst: D)-> Result<(), AlpsError>
+where
+    S: AsRef<Path>,
+    D: AsRef<Path>,
+{
+    let path = Path::new(src.as_ref());
 
-            for package in get_entries(&text){
-                if arg == mutate(package){
-                    found.push(package);
-                }
+    if path.is_dir(){
+        for dir in fs::read_dir(src.as_ref())?{
+            let dir = dir?;
+            if dir.file_type()?.is_symlink(){
+                continue;
             }
-
-            let label = &label[1..label.len()-1].to_lowercase();
-            if !found.is_empty(){
-                for found in found{
-                    println!(
-                        "{} Found {}/{}/{} ",
-                        "[?]".blue(),
-                        group.blue(),
-                        label.blue(),
-                        found.blue()
-                    );
+            copy_dir(dir.path(), dst.as_ref().join(dir.file_name()))?;
+        }
+    }
+    else if path.is_file(){
+        if let Some(parent) = dst.as_ref().parent(){
+            if let Err(error) = fs::create_dir_all(parent){
+                if error.kind() != ErrorKind::PermissionDenied{
+                    return Err(error.into());
                 }
-            }
-            else{
-                eprintln!(
-                    "{} {}/{}/{} not found!",
-                    "[!]".yellow(),
-                    group.yellow(),
-                    label.yellow(),
-                    arg.yellow()
-                );
-                status += 1;
+                sudo(Command::new("sudo").args(["mkdir", "-p"]).arg(parent), parent)?;
             }
         }
 
-        std::process::exit(status);
-    }
-    else{
-        let mut count = 0;
-        for package in get_entries(&text){
-            print!("{}, ", mutate(package).blue());
-            count += 1;
+        if let Err(error) = fs::copy(&src, &dst){
+            if error.kind() != ErrorKind::PermissionDenied{
+                return Err(error.into());
+            }
+
+            sudo(
+                Command::new("sudo")
+                    .args(["cp", "-r", "-T"])
+                    .arg(src.as_ref())
+                    .arg(dst.as_ref()),
+                dst.as_ref()
+            )?;
         }
-        println!("({count}) entries found...");
     }
+    Ok(())
 }
 
-pub fn reformat_config(labels: &Vec<String>, group: &str, home_dir: &str){
-    let mut config_text = String::new();
-
-    for label in labels{
-        config_text.push_str(&(label.to_owned() + "\n"));
-
-        for entry in get_entries(&read_label(label, group, home_dir)){
-            config_text.push_str(&(entry.to_owned() + "\n"));
-        }
-
-        if label != labels.last().unwrap(){
-            config_text.push('\n');
-        }
-    }
-    config_text.push('\n');
+// Writes contents to path through copy_dir, picking up its sudo fallback. The temporary
+// copy is only readable by the user.
+pub fn write_file(path: &Path, contents: &[u8])-> Result<(), AlpsError>{
+    let temp = env::temp_dir().join("alps-".to_owned() + &process::id().to_string());
 
-    let mut handle = fs::OpenOptions::new()
-        .truncate(true)
+    OpenOptions::new()
         .write(true)
-        .open(home_dir.to_owned() + group + "/" + group + ".conf")
-        .unwrap();
+        .create_new(true)
+        .mode(0o600)
+        .open(&temp)?
+        .write_all(contents)?;
+
+    let result = copy_dir(&temp, path);
+    let _ = fs::remove_file(&temp);
 
-    handle.write_all(config_text.as_bytes()).unwrap();
+    result
 }
 
-pub fn copy_dir<S, D>(src: S, dst: D)
+// The (source, destination) file pairs copy_dir would copy, without copying them.
+pub fn copy_targets<S, D>(src: S, dst: D)-> Result<Vec<(PathBuf, PathBuf)>, AlpsError>
 where
     S: AsRef<Path>,
     D: AsRef<Path>,
 {
     let path = Path::new(src.as_ref());
+    let mut targets = Vec::new();
 
     if path.is_dir(){
-        for dir in fs::read_dir(src).unwrap(){
-            let dir = dir.unwrap();
-            copy_dir(dir.path(), dst.as_ref().join(dir.file_name()));
+        for dir in fs::read_dir(src.as_ref())?{
+            let dir = dir?;
+            if dir.file_type()?.is_symlink(){
+                continue;
+            }
+            targets.append(&mut copy_targets(dir.path(), dst.as_ref().join(dir.file_name()))?);
         }
+        targets.sort();
     }
     else if path.is_file(){
-        let parent = dst.as_ref().parent().unwrap();
-
-        if let Err(error) = fs::create_dir_all(parent){
-            if error.kind() == ErrorKind::PermissionDenied
-                &&
-                Command::new("sudo")
-                    .args(["mkdir", "-p", parent.to_str().unwrap()])
-                    .output()
-                    .is_err()
-            {
-                eprintln!(
-                    "{} Command ({}) failed to run!",
-                    "[!!!]".red(),
-                    "mkdir".red()
-                );
-                std::process::exit(1);
-            }
-        }
-
-        if let Err(error) = fs::copy(&src, &dst){
-            if error.kind() == ErrorKind::PermissionDenied
-                && 
-                Command::new("sudo")
-                    .args(["cp", "-r", "-T", src.as_ref().as_os_str().to_str().unwrap(), dst.as_ref().as_os_str().to_str().unwrap()])
-                    .output()
-                    .is_err()
-            {
-                eprintln!(
-                    "{} Command ({}) failed to run!",
-                    "[!!!]".red(),
-                    "cd".red()
-                );
-                std::process::exit(1);
-            }
-        }
+        targets.push((path.to_path_buf(), dst.as_ref().to_path_buf()));
     }
+    Ok(targets)
 }
 
-pub fn read_label(label: &str, group:  &str, home_dir: &str)-> String{
-    let excludes = vec![
-        String::from("[PACKAGES]"),
-        String::from("[CONFIGS]"),
-        String::from("[SCRIPTS]")
-    ];
+// UTC time as YYYYMMDD-HHMMSS, which sorts by age.
+pub fn timestamp()-> String{
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs() as i64)
+        .unwrap_or_default();
 
-    let mut text = String::new();
+    stamp(seconds)
+}
 
-    let mut handle = fs::OpenOptions::new()
-        .read(true)
-        .write(true)
-        .create(true)
-        .open(home_dir.to_owned() + group + "/" + group + ".conf")
-        .unwrap();
-    handle.read_to_string(&mut text).unwrap();
-    let text: Vec<&str> = text.split(&label).collect();
+fn stamp(seconds: i64)-> String{
+    let (days, time) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+    // Days since the epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era/1460 + day_of_era/36524 - day_of_era/146096)/365;
+    let day_of_year = day_of_era - (365*year_of_era + year_of_era/4 - year_of_era/100);
+    let month = (5*day_of_year + 2)/153;
+    let day = day_of_year - (153*month + 2)/5 + 1;
+    let month = if month < 10{ month + 3 } else{ month - 9 };
+    let year = year_of_era + era*400 + if month <= 2{ 1 } else{ 0 };
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        time/3600,
+        time%3600/60,
+        time%60
+    )
+}
 
-    if text.len() > 1{
-        let mut text = text[1];
+// Entries of a manifest section, one per line.
+pub fn read_label(label: &str, group:  &str, home_dir: &str)-> Result<String, AlpsError>{
+    Ok(Manifest::load(group, home_dir)?.entries(label).join("\n"))
+}
 
-        for exclude in &excludes{
-            text = text.split(exclude).next().unwrap();
-        }
+pub fn config_write(group: &str, label: &str, entry: &str, home_dir: &str, mode: bool)-> Result<(), AlpsError>{
+    let mut manifest = Manifest::load(group, home_dir)?;
 
-        text.to_string()
+    if mode{
+        manifest.insert(label, entry)?;
     }
     else{
-        String::new()
+        manifest.remove(label, entry);
     }
-}
 
-pub fn config_write(group: &str, label: &str, entry: &str, home_dir: &str, mode: bool){
-    let excludes = vec![
-        String::from("[PACKAGES]"),
-        String::from("[CONFIGS]"),
-        String::from("[SCRIPTS]")
-    ];
+    manifest.save(group, home_dir)
+}
 
-    reformat_config(&excludes, group, home_dir);
+#[cfg(test)]
+mod tests{
+    use super::*;
 
-    let mut segments: Vec<String> = Vec::new();
-    for segment in &excludes{
-        let list = 
-            if mode{
-                read_label(segment, group, home_dir)
-            }
-            else{
-                get_entries(&read_label(segment, group, home_dir)).filter_map(|line|{
-                    if *line != *entry{
-                        Some(line.to_owned() + "\n")
-                    }
-                    else{
-                        None
-                    }
-                }).collect::<String>()
-            };
-        
-        if segment == label && mode{
-            segments.push(segment.to_owned() + "\n" + entry + &list);
-        }
-        else{
-            segments.push(segment.to_owned() + &list);
-        }
+    #[test]
+    fn stamp_epoch(){
+        assert_eq!(stamp(0), "19700101-000000");
     }
 
-    let mut handle = fs::OpenOptions::new()
-        .truncate(true)
-        .write(true)
-        .open(home_dir.to_owned() + group + "/" + group + ".conf")
-        .unwrap();           
+    #[test]
+    fn stamp_leap_days(){
+        assert_eq!(stamp(951782400), "20000229-000000");
+        assert_eq!(stamp(1709251199), "20240229-235959");
+        // 2100 is no leap year.
+        assert_eq!(stamp(4107542399), "21000228-235959");
+        assert_eq!(stamp(4107542400), "21000301-000000");
+    }
 
-    for mut segment in segments{
-        if !segment.ends_with('\n'){
-            segment.push('\n');
-        }
+    #[test]
+    fn timestamp_format(){
+        let stamp = timestamp();
 
-        handle.write_all(segment.as_bytes()).unwrap();
+        assert_eq!(stamp.len(), 15);
+        assert_eq!(&stamp[8..9], "-");
+        assert!(stamp.chars().filter(|char| *char != '-').all(|char| char.is_ascii_digit()));
     }
-
-    reformat_config(&excludes, group, home_dir);
 }